@@ -0,0 +1,73 @@
+//! Compares the `PartialEq` generated by `CmpBy`, which tests the keys with `==`,
+//! to the `self.cmp(other).is_eq()` implementation it replaced.
+
+use cmp_by_derive::CmpBy;
+use core::cmp::Ordering;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[derive(CmpBy, Clone)]
+struct Derived {
+    #[cmp_by]
+    name: String,
+    #[cmp_by]
+    tags: Vec<u32>,
+    #[cmp_by]
+    id: u64,
+}
+
+#[derive(Clone)]
+struct ThroughCmp(Derived);
+
+impl PartialEq for ThroughCmp {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for ThroughCmp {}
+
+impl PartialOrd for ThroughCmp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ThroughCmp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+fn records() -> Vec<Derived> {
+    (0..1024u32)
+        .map(|i| Derived {
+            // Finding the ordering of strings with a long common prefix needs to compare the
+            // prefix, while strings of different lengths are known to differ right away.
+            name: format!("/var/lib/records/{}", "0".repeat(256 + i as usize % 3)),
+            tags: (0..32).map(|t| t * (i % 4)).collect(),
+            id: i.into(),
+        })
+        .collect()
+}
+
+/// Counts the adjacent duplicates, like `Vec::dedup` would find them.
+fn count_duplicates<T: PartialEq>(values: &[T]) -> usize {
+    values.windows(2).filter(|w| w[0] == w[1]).count()
+}
+
+fn bench_eq(c: &mut Criterion) {
+    let derived = records();
+    let through_cmp = derived.iter().cloned().map(ThroughCmp).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("eq");
+    group.bench_function("field ==", |b| {
+        b.iter(|| count_duplicates(black_box(&derived)))
+    });
+    group.bench_function("cmp().is_eq()", |b| {
+        b.iter(|| count_duplicates(black_box(&through_cmp)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_eq);
+criterion_main!(benches);
@@ -1,72 +1,163 @@
-use crate::parsing::{parse_input, ParsedFields, ParsedInput, ParsingError};
-use proc_macro2::TokenStream;
-use quote::{quote, quote_spanned, ToTokens};
-use syn::{parse2, parse_quote_spanned, spanned::Spanned, DeriveInput, Error, Expr, Index};
+use crate::eq_by::gen_eq_expression;
+use crate::parsing::{
+    bounded_generics, parse_input, CmpKey, Derive, FieldPath, FloatOrder, Item, Key, KeyOptions,
+    Operand, ParsedFields, ParsedInput, ParsedVariant, ParsingError, Position,
+};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse2, parse_quote, parse_quote_spanned, spanned::Spanned, DeriveInput, Error, Expr, Ident,
+    Member, Type,
+};
+
+/// Comparison traits that can be derived from the `#[cmp_by]` keys.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CmpTrait {
+    Eq,
+    PartialEq,
+    PartialOrd,
+    Ord,
+}
 
 pub fn impl_cmp_by_derive(input: DeriveInput) -> TokenStream {
+    impl_cmp_traits(
+        input,
+        Derive::CmpBy,
+        "CmpBy",
+        &[
+            CmpTrait::Eq,
+            CmpTrait::PartialEq,
+            CmpTrait::PartialOrd,
+            CmpTrait::Ord,
+        ],
+    )
+}
+
+/// Implements `traits` from the keys of `derive`'s attribute, `derive_name` being the derive macro reported in errors.
+///
+/// When `Ord` is implemented along, `PartialEq` and `PartialOrd` call `Ord::cmp`, otherwise they compare the keys themselves.
+pub fn impl_cmp_traits(
+    input: DeriveInput,
+    derive: Derive,
+    derive_name: &str,
+    traits: &[CmpTrait],
+) -> TokenStream {
     // println!("Entered impl_cmp_by_derive");
     let input_span = input.span();
     let struct_name = input.ident.clone();
 
+    match parse_keys(input, derive, derive_name) {
+        Ok(parsed) => gen_cmp_traits(&parsed, &struct_name, input_span, derive_name, traits),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+/// Parses the keys of `derive`'s attribute, reporting a missing key against `derive_name`.
+pub fn parse_keys(
+    input: DeriveInput,
+    derive: Derive,
+    derive_name: &str,
+) -> Result<ParsedInput, Error> {
+    parse_input(input, derive).map_err(|err| match err {
+        ParsingError::Error(err) => err,
+        ParsingError::NoField(span) => Error::new(
+            span,
+            format!(
+                "{derive_name}: no field to compare on. Mark fields to compare on with #[{}]",
+                derive.attr()
+            ),
+        ),
+    })
+}
+
+/// Implements `traits` for `struct_name` from its parsed keys.
+pub fn gen_cmp_traits(
+    parsed: &ParsedInput,
+    struct_name: &Ident,
+    input_span: Span,
+    derive_name: &str,
+    traits: &[CmpTrait],
+) -> TokenStream {
+    let prelude = parsed.prelude();
     let ParsedInput {
         expressions: sortable_expressions,
         fields: sortable_fields,
         generics,
-        generic_arguments: generics_params,
-    } = match parse_input(input, "cmp_by") {
-        Ok(value) => value,
-        Err(err) => {
-            return match err {
-                ParsingError::Error(err) => err,
-                ParsingError::NoField(span) => Error::new(
-                    span,
-                    "CmpBy: no field to compare on. Mark fields to compare on with #[cmp_by]",
-                ),
-            }
-            .into_compile_error()
-        }
-    };
+        discriminant_type,
+        bounded_params,
+        bounds,
+        packed,
+        allow_inconsistent_hash: _,
+        cmp_key,
+        members,
+    } = parsed;
+    let packed = packed.is_some();
     // println!("Successfully parsed input");
+    let variant_placed = sortable_expressions
+        .iter()
+        .any(|key| key.value.is_variant_keyword());
 
-    let field_ord_statement = match &sortable_fields {
-        ParsedFields::Struct(sortable_expr) => gen_cmp_exprs(sortable_expr),
+    let field_ord_statement = match sortable_fields {
+        ParsedFields::Struct(sortable_fields) => gen_cmp_exprs(sortable_fields.iter().map(
+            |Key {
+                 value: member,
+                 options,
+             }| {
+                gen_cmp(
+                    Operand::field(quote!(self), member, packed),
+                    Operand::field(quote!(other), member, packed),
+                    options,
+                )
+            },
+        )),
+        // Enums without variants have no values to compare.
+        fields if fields.is_empty_enum() => Some(parse_quote_spanned! {input_span => {
+            let _ = other;
+            match *self {}
+        }}),
         ParsedFields::Enum(sortable_variants) => {
             // dbg!(sortable_variants.len());
             let ord_statements = sortable_variants
                 .iter()
-                .filter(|(_, sortable_expr)| !sortable_expr.is_empty())
-                .map(|(variant, sortable_expr)| {
+                .filter(|variant| !variant.fields.is_empty())
+                .map(|variant| {
+                    let this_pattern = variant.binding_pattern("this");
+                    let other_pattern = variant.binding_pattern("other");
                     let ord_pattern =
-                        quote_spanned! {variant.span() => (this @ #variant, other @ #variant)};
-                    let ord_statement = gen_cmp_exprs(sortable_expr);
+                        quote_spanned! {variant.ident.span() => (#this_pattern, #other_pattern)};
+                    let ord_statement = gen_cmp_exprs(variant.fields.iter().map(
+                        |Key {
+                             value: path,
+                             options,
+                         }| {
+                            gen_cmp(
+                                Operand::binding("this", path),
+                                Operand::binding("other", path),
+                                options,
+                            )
+                        },
+                    ));
                     quote! {#ord_pattern => #ord_statement}
                 });
 
-            let idx_statements =
-                sortable_variants
-                    .iter()
-                    .enumerate()
-                    .map(|(var_idx, (variant, _))| {
-                        let idx_statement = Index::from(var_idx);
-                        quote! {#variant => #idx_statement}
-                    });
-            let idx_statements = quote! {
-                #(#idx_statements,)*
-            };
-            // What do we compare when we have different variants? We compare the discriminant based on the order of variant declaration.
+            // What do we compare when we have different variants? We compare their ranks, which follow the order of variant declaration by default.
+            // When `_variant` places that comparison among the container-level items, it has already been done or will be.
             // TODO: And what about variants that have no fields marked to cmp?
+            let variant_cmp = if variant_placed {
+                quote!(_ => ::core::cmp::Ordering::Equal)
+            } else {
+                let variant_cmp = gen_variant_cmp(
+                    sortable_variants,
+                    discriminant_type.as_ref(),
+                    quote!(this),
+                    quote!(other),
+                );
+                quote!((this, other) => #variant_cmp)
+            };
             let stream = quote_spanned! { input_span =>
                 match (self, other) {
                     #(#ord_statements,)*
-                    (this, other) => {
-                        match this {
-                            #idx_statements
-                        }.cmp(
-                            &match other {
-                                #idx_statements
-                            }
-                        )
-                    }
+                    #variant_cmp
                 }
             };
             // println!("{stream}");
@@ -80,21 +171,36 @@ pub fn impl_cmp_by_derive(input: DeriveInput) -> TokenStream {
         }
     };
     // println!("Successfully generated field cmps");
+    let delegate_to_ord = traits.contains(&CmpTrait::Ord);
 
     // println!("Entering gen_cmp_expr");
     let expr_ord_statements = sortable_expressions
         .iter()
-        .map(|expr| {
-            if expr.to_token_stream().to_string() == "_fields" {
-                parse_quote_spanned! { expr.span() =>
-                    #field_ord_statement
+        .filter_map(
+            |Key {
+                 value: expr,
+                 options,
+             }| {
+                if expr.is_fields_keyword() {
+                    return field_ord_statement.clone();
                 }
-            } else {
-                parse_quote_spanned! { expr.span() =>
-                    self.#expr.cmp(&other.#expr)
+                if let (true, ParsedFields::Enum(variants)) =
+                    (expr.is_variant_keyword(), sortable_fields)
+                {
+                    return Some(gen_variant_cmp(
+                        variants,
+                        discriminant_type.as_ref(),
+                        quote!(self),
+                        quote!(other),
+                    ));
                 }
-            }
-        })
+                Some(gen_cmp(
+                    Operand::item(quote!(self), expr, packed),
+                    Operand::item(quote!(other), expr, packed),
+                    options,
+                ))
+            },
+        )
         .reduce(|ord_expr: Expr, expr| {
             // println!("Combining {} with {}", quote!(#ord_expr), quote!(#expr));
             parse_quote_spanned! {expr.span() =>
@@ -103,67 +209,534 @@ pub fn impl_cmp_by_derive(input: DeriveInput) -> TokenStream {
         });
     // println!("Successfully generated preceding expressions cmps");
 
+    // Fields placed among the container-level items with `_fields` are not compared again.
+    let fields_placed = sortable_expressions
+        .iter()
+        .any(|key| key.value.is_fields_keyword());
+    let field_ord_statement = field_ord_statement.filter(|_| !fields_placed);
     let ord_expression = match (expr_ord_statements, field_ord_statement) {
         (Some(exprs), Some(fields)) => {
+            let exprs = receiver(exprs);
             parse_quote_spanned! {input_span =>
                 #exprs.then_with(|| #fields)
             }
         }
         (None, Some(ts)) | (Some(ts), None) => ts,
-        (None, None) => {
-            unreachable!("Error of no fields to compare on should be handled in the parsing stage.")
-        }
+        // Values of unit structs are all equal.
+        (None, None) => parse_quote_spanned! {input_span => {
+            let _ = other;
+            ::core::cmp::Ordering::Equal
+        }},
     };
     // println!("Successfully combined preceding expressions with fields cmps");
 
-    let where_clause = &generics.where_clause;
-    let generics_params = &generics_params;
+    // `cmp_key` is generated along with `Ord`, which then compares the keys it returns.
+    let cmp_key = match (cmp_key, sortable_fields) {
+        (Some(CmpKey { name, .. }), _) if !delegate_to_ord => {
+            return Error::new(
+                name.span(),
+                format!(
+                    "`cmp_key` is generated along with `Ord`, which {derive_name} doesn't derive"
+                ),
+            )
+            .into_compile_error();
+        }
+        (Some(CmpKey { name, vis }), ParsedFields::Struct(fields)) => {
+            match gen_cmp_key(name, sortable_expressions, fields, members, packed) {
+                Ok((ty, value)) => Some((name, vis, ty, value)),
+                Err(err) => return err.into_compile_error(),
+            }
+        }
+        _ => None,
+    };
 
-    quote_spanned! {input_span =>
-        impl #generics ::core::cmp::Eq for #struct_name <#(#generics_params),*> #where_clause {}
+    // Equality is tested on the same keys, without the cost of finding out which value is greater.
+    let eq_expression = gen_eq_expression(
+        sortable_expressions,
+        sortable_fields,
+        packed,
+        input_span,
+        gen_cmp_eq,
+    );
+    let lt_methods = gen_lt_methods(
+        sortable_expressions,
+        sortable_fields,
+        packed,
+        delegate_to_ord,
+        &ord_expression,
+        &prelude,
+    );
 
-        impl #generics ::core::cmp::PartialEq<Self> for #struct_name <#(#generics_params),*> #where_clause {
-            #[inline]
-            fn eq(&self, other: &Self) -> bool {
-                self.cmp(other).is_eq()
+    let impls = [
+        CmpTrait::Eq,
+        CmpTrait::PartialEq,
+        CmpTrait::PartialOrd,
+        CmpTrait::Ord,
+    ]
+    .into_iter()
+    .filter(|t| traits.contains(t))
+    .map(|t| {
+        // Orderings compare the keys with `Ord::cmp`, even for `PartialOrd`.
+        let bound = match t {
+            CmpTrait::Eq => quote!(::core::cmp::Eq),
+            CmpTrait::PartialEq => quote!(::core::cmp::PartialEq),
+            CmpTrait::PartialOrd | CmpTrait::Ord => quote!(::core::cmp::Ord),
+        };
+        let generics = bounded_generics(generics, bounded_params, bounds, bound);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        match t {
+        CmpTrait::Eq => quote_spanned! {input_span =>
+            impl #impl_generics ::core::cmp::Eq for #struct_name #ty_generics #where_clause {}
+        },
+        CmpTrait::PartialEq => quote_spanned! {input_span =>
+            impl #impl_generics ::core::cmp::PartialEq<Self> for #struct_name #ty_generics #where_clause {
+                #[inline]
+                fn eq(&self, other: &Self) -> bool {
+                    #prelude
+                    #eq_expression
+                }
+            }
+        },
+        CmpTrait::PartialOrd => {
+            // Wrapping the comparison of an enum without variants in `Some` would be unreachable.
+            let partial_ord_expression = if delegate_to_ord {
+                quote!(::core::option::Option::Some(self.cmp(other)))
+            } else if sortable_fields.is_empty_enum() {
+                quote!(#ord_expression)
+            } else {
+                quote! {
+                    #prelude
+                    ::core::option::Option::Some(#ord_expression)
+                }
+            };
+            quote_spanned! {input_span =>
+                impl #impl_generics ::core::cmp::PartialOrd<Self> for #struct_name #ty_generics #where_clause {
+                    #[inline]
+                    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                        #partial_ord_expression
+                    }
+
+                    #lt_methods
+                }
+            }
+        }
+        CmpTrait::Ord => if let Some((name, vis, ty, value)) = &cmp_key {
+            quote_spanned! {input_span =>
+                impl #impl_generics #struct_name #ty_generics #where_clause {
+                    /// Keys the values are ordered by, in the order they are compared.
+                    #[inline]
+                    #vis fn #name(&self) -> #ty {
+                        #prelude
+                        #value
+                    }
+                }
+                impl #impl_generics ::core::cmp::Ord for #struct_name #ty_generics #where_clause {
+                    #[inline]
+                    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                        ::core::cmp::Ord::cmp(&self.#name(), &other.#name())
+                    }
+                }
             }
+        } else {
+            quote_spanned! {input_span =>
+                impl #impl_generics ::core::cmp::Ord for #struct_name #ty_generics #where_clause {
+                    #[inline]
+                    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                        #prelude
+                        #ord_expression
+                    }
+                }
+            }
+        },
         }
+    });
 
-        impl #generics ::core::cmp::PartialOrd<Self> for #struct_name <#(#generics_params),*> #where_clause {
-            #[inline]
-            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
-                ::core::option::Option::Some(self.cmp(other))
+    quote!(#(#impls)*)
+}
+
+/// Return type and value of `cmp_key`: the keys of a struct in the order they are compared, in a tuple that
+/// compares like them.
+///
+/// Fields are borrowed with the type the struct declares, or copied out of a packed struct, while other keys are values
+/// of an opaque `Ord` type. Descending keys are wrapped in `Reverse`.
+fn gen_cmp_key(
+    name: &Ident,
+    expressions: &[Key<Item>],
+    fields: &[Key<Member>],
+    members: &[(Member, Type)],
+    packed: bool,
+) -> syn::Result<(TokenStream, TokenStream)> {
+    // Each key with its span, the struct member it is, if any, and whether it can be borrowed.
+    let field_keys = || {
+        fields.iter().map(|Key { value, options }| {
+            let operand = Operand::field(quote!(self), value, packed);
+            (operand, value.span(), Some(value.clone()), true, options)
+        })
+    };
+    let mut keys = Vec::new();
+    for Key { value, options } in expressions {
+        if value.is_fields_keyword() {
+            keys.extend(field_keys());
+            continue;
+        }
+        let (member, place) = match value {
+            Item::Expr(expr) => (
+                FieldPath::parse(expr)
+                    .ok()
+                    .filter(|field| field.path.is_empty())
+                    .map(|field| field.member),
+                matches!(expr, Expr::Path(_) | Expr::Field(_) | Expr::Lit(_)),
+            ),
+            Item::Value(_) | Item::Common(_) => (None, false),
+        };
+        keys.push((
+            Operand::item(quote!(self), value, packed),
+            value.span(),
+            member,
+            place,
+            options,
+        ));
+    }
+    if !expressions.iter().any(|key| key.value.is_fields_keyword()) {
+        keys.extend(field_keys());
+    }
+
+    let mut types = Vec::new();
+    let mut values = Vec::new();
+    for (operand, span, member, place, options) in keys {
+        // `Option` places `None` first, and `Reverse` last, so only the other placement has no `Ord` value.
+        let against_direction = if options.is_descending() {
+            Position::First
+        } else {
+            Position::Last
+        };
+        if options.with.is_some()
+            || options.float.is_some()
+            || options.none == Some(against_direction)
+        {
+            return Err(Error::new(
+                span,
+                "`cmp_key` cannot return a key compared `with` a function, by a float ordering, with `none_last` \
+                 ascending or with `none_first` descending, which has no `Ord` value: project it with `key = ...` \
+                 instead",
+            ));
+        }
+        let opaque = quote!(impl ::core::cmp::Ord + '_);
+        let member_type = member.and_then(|member| {
+            members
+                .iter()
+                .find(|(field, _)| *field == member)
+                .map(|(_, ty)| ty)
+        });
+        let (ty, value) = if options.key.is_some() {
+            (opaque, operand.project(options).receiver)
+        } else if !place || packed {
+            (
+                member_type.map_or(opaque, |ty| quote!(#ty)),
+                operand.receiver,
+            )
+        } else {
+            (
+                member_type.map_or(opaque, |ty| quote!(&#ty)),
+                operand.reference,
+            )
+        };
+        if options.is_descending() {
+            types.push(quote!(::core::cmp::Reverse<#ty>));
+            values.push(quote!(::core::cmp::Reverse(#value)));
+        } else {
+            types.push(ty);
+            values.push(quote!(#value));
+        }
+    }
+    // Tuples only implement `Ord` up to 12 elements.
+    if types.len() > 12 {
+        return Err(Error::new(
+            name.span(),
+            format!(
+                "`cmp_key` returns the keys as a tuple, which is only `Ord` up to 12 keys, not {}",
+                types.len()
+            ),
+        ));
+    }
+    Ok((quote!((#(#types,)*)), quote!((#(#values,)*))))
+}
+
+/// Compares the ranks of the variants of `this` and `other`, which are values of `discriminant_type` if given.
+fn gen_variant_cmp(
+    variants: &[ParsedVariant],
+    discriminant_type: Option<&Ident>,
+    this: TokenStream,
+    other: TokenStream,
+) -> Expr {
+    if variants.is_empty() {
+        return parse_quote!(match *#this {});
+    }
+    let idx_statements = variants.iter().map(|variant| {
+        let pattern = variant.pattern();
+        let rank = &variant.rank;
+        quote! {#pattern => #rank}
+    });
+    let idx_statements = quote! {
+        #(#idx_statements,)*
+    };
+    if let Some(discriminant_type) = discriminant_type {
+        // Naming the type gives it to the discriminant values, which can be any constant expression.
+        return parse_quote! {
+            <#discriminant_type as ::core::cmp::Ord>::cmp(
+                &match #this {
+                    #idx_statements
+                },
+                &match #other {
+                    #idx_statements
+                },
+            )
+        };
+    }
+    // The parentheses allow the comparison to be the first of the chain, at the start of a statement.
+    parse_quote! {
+        (match #this {
+            #idx_statements
+        }).cmp(
+            &match #other {
+                #idx_statements
             }
+        )
+    }
+}
+
+/// Tests `this` and `other` for equality, consistently with [`gen_cmp`].
+fn gen_cmp_eq(this: Operand, other: Operand, options: &KeyOptions) -> Expr {
+    let span = this.receiver.span();
+    let (this, other) = (this.project(options), other.project(options));
+    if options.none.is_none() || (options.with.is_none() && options.float.is_none()) {
+        // Without `with` or a float ordering, `Option`'s own equality agrees with the ordering.
+        return gen_value_eq(this, other, options);
+    }
+    let some_eq = gen_value_eq(
+        Operand::reference(parse_quote!(this)),
+        Operand::reference(parse_quote!(other)),
+        options,
+    );
+    let (this, other) = (this.reference, other.reference);
+    parse_quote_spanned! { span =>
+        match (#this, #other) {
+            (::core::option::Option::Some(this), ::core::option::Option::Some(other)) => #some_eq,
+            (::core::option::Option::None, ::core::option::Option::None) => true,
+            _ => false,
         }
+    }
+}
 
-        impl #generics ::core::cmp::Ord for #struct_name <#(#generics_params),*> #where_clause {
-            #[inline]
-            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
-                #ord_expression
+/// Tests the values of `this` and `other` for equality, once they are known not to be `None`.
+fn gen_value_eq(this: Operand, other: Operand, options: &KeyOptions) -> Expr {
+    let span = this.receiver.span();
+    if let Some(with) = &options.with {
+        let (this, other) = (this.reference, other.reference);
+        return parse_quote_spanned! { span =>
+            #with(#this, #other).is_eq()
+        };
+    }
+    match options.float {
+        Some(FloatOrder::Total) => {
+            let (this, other) = (this.receiver, other.reference);
+            parse_quote_spanned! { span =>
+                #this.total_cmp(#other).is_eq()
+            }
+        }
+        Some(FloatOrder::Nan(_)) => {
+            let (this, other) = (this.reference, other.reference);
+            parse_quote_spanned! { span =>
+                match (#this, #other) {
+                    (this, other) => this == other || this.is_nan() && other.is_nan(),
+                }
+            }
+        }
+        None => {
+            let (this, other) = (this.receiver, other.receiver);
+            parse_quote_spanned! { span =>
+                #this == #other
             }
         }
     }
 }
 
-fn gen_cmp_exprs(sortable_expr: &[Expr]) -> Option<Expr> {
-    // println!("Entering gen_cmp_expr");
-    sortable_expr
-        .iter()
-        .map(|expr| {
-            parse_quote_spanned! { expr.span() =>
-                self.#expr.cmp(&other.#expr)
+/// `PartialOrd::lt`, `le`, `gt` and `ge`, which skip the `Option` returned by `partial_cmp`.
+///
+/// A struct compared on a single key without options uses the key's own operators.
+fn gen_lt_methods(
+    expressions: &[Key<Item>],
+    fields: &ParsedFields,
+    packed: bool,
+    delegate_to_ord: bool,
+    ord_expression: &Expr,
+    prelude: &TokenStream,
+) -> TokenStream {
+    let single_key = match (expressions, fields) {
+        ([Key { value, options }], ParsedFields::Struct(fields))
+            if fields.is_empty() && !value.is_fields_keyword() =>
+        {
+            Some((
+                Operand::item(quote!(self), value, packed),
+                Operand::item(quote!(other), value, packed),
+                options,
+            ))
+        }
+        ([], ParsedFields::Struct(fields)) => match fields.as_slice() {
+            [Key { value, options }] => Some((
+                Operand::field(quote!(self), value, packed),
+                Operand::field(quote!(other), value, packed),
+                options,
+            )),
+            _ => None,
+        },
+        _ => None,
+    }
+    .filter(|(_, _, options)| {
+        options.with.is_none() && options.float.is_none() && options.none.is_none()
+    });
+    let methods = [
+        (quote!(lt), quote!(<), quote!(is_lt)),
+        (quote!(le), quote!(<=), quote!(is_le)),
+        (quote!(gt), quote!(>), quote!(is_gt)),
+        (quote!(ge), quote!(>=), quote!(is_ge)),
+    ];
+    let methods = methods.into_iter().map(|(method, operator, is_ordering)| {
+        let body = if let Some((this, other, options)) = &single_key {
+            let (this, other) = (
+                this.clone().project(options).receiver,
+                other.clone().project(options).receiver,
+            );
+            if options.is_descending() {
+                quote!(#prelude #other #operator #this)
+            } else {
+                quote!(#prelude #this #operator #other)
             }
-        })
-        .reduce(|ord_expr: Expr, expr| {
-            // println!("Combining {} with {}", quote!(#ord_expr), quote!(#expr));
-            parse_quote_spanned! {expr.span() =>
-                #ord_expr.then_with(|| #expr)
+        } else if delegate_to_ord {
+            quote!(self.cmp(other).#is_ordering())
+        } else if fields.is_empty_enum() {
+            quote!(#ord_expression)
+        } else {
+            let ord_expression = receiver(ord_expression.clone());
+            quote!(#prelude #ord_expression.#is_ordering())
+        };
+        quote! {
+            #[inline]
+            fn #method(&self, other: &Self) -> bool {
+                #body
             }
-        })
+        }
+    });
+    quote!(#(#methods)*)
+}
+
+/// Compares `this` to `other` according to the key's options.
+fn gen_cmp(this: Operand, other: Operand, options: &KeyOptions) -> Expr {
+    let span = this.receiver.span();
+    let (this, other) = (this.project(options), other.project(options));
+    let Some(position) = options.none else {
+        return gen_float_cmp(this, other, options);
+    };
+    // `None` is placed regardless of the sort direction, which only applies to the values.
+    let (less, greater) = match position {
+        Position::First => (quote!(Less), quote!(Greater)),
+        Position::Last => (quote!(Greater), quote!(Less)),
+    };
+    let some_cmp = gen_float_cmp(
+        Operand::reference(parse_quote!(this)),
+        Operand::reference(parse_quote!(other)),
+        options,
+    );
+    let (this, other) = (this.reference, other.reference);
+    parse_quote_spanned! { span =>
+        match (#this, #other) {
+            (::core::option::Option::Some(this), ::core::option::Option::Some(other)) => #some_cmp,
+            (::core::option::Option::None, ::core::option::Option::None) => ::core::cmp::Ordering::Equal,
+            (::core::option::Option::None, ::core::option::Option::Some(_)) => ::core::cmp::Ordering::#less,
+            (::core::option::Option::Some(_), ::core::option::Option::None) => ::core::cmp::Ordering::#greater,
+        }
+    }
+}
+
+/// Compares `this` to `other`, placing NaNs according to the key's options.
+fn gen_float_cmp(this: Operand, other: Operand, options: &KeyOptions) -> Expr {
+    let span = this.receiver.span();
+    let Some(FloatOrder::Nan(position)) = options.float else {
+        return gen_value_cmp(this, other, options);
+    };
+    // NaNs are placed regardless of the sort direction, which only applies to numbers.
+    let nan_cmp: Expr = match position {
+        Position::First => parse_quote!(other.is_nan().cmp(&this.is_nan())),
+        Position::Last => parse_quote!(this.is_nan().cmp(&other.is_nan())),
+    };
+    let value_cmp = gen_value_cmp(
+        Operand::reference(parse_quote!(this)),
+        Operand::reference(parse_quote!(other)),
+        options,
+    );
+    let (this, other) = (this.reference, other.reference);
+    parse_quote_spanned! { span =>
+        match (#this, #other) {
+            (this, other) => #nan_cmp.then_with(|| #value_cmp),
+        }
+    }
+}
+
+/// Compares the values of `this` and `other`, once they are known not to be `None` or NaN.
+fn gen_value_cmp(this: Operand, other: Operand, options: &KeyOptions) -> Expr {
+    let span = this.receiver.span();
+    let cmp: Expr = if let Some(with) = &options.with {
+        let (this, other) = (this.reference, other.reference);
+        parse_quote_spanned! { span =>
+            #with(#this, #other)
+        }
+    } else {
+        let (this, other) = (this.receiver, other.reference);
+        match options.float {
+            Some(FloatOrder::Total) => parse_quote_spanned! { span =>
+                #this.total_cmp(#other)
+            },
+            Some(FloatOrder::Nan(_)) => parse_quote_spanned! { span =>
+                #this.partial_cmp(#other).unwrap_or(::core::cmp::Ordering::Equal)
+            },
+            None => parse_quote_spanned! { span =>
+                #this.cmp(#other)
+            },
+        }
+    };
+    if options.is_descending() {
+        let cmp = receiver(cmp);
+        parse_quote_spanned! { span =>
+            #cmp.reverse()
+        }
+    } else {
+        cmp
+    }
+}
+
+/// Chains the comparisons in order, each one only being evaluated when the previous ones are equal.
+fn gen_cmp_exprs(comparisons: impl IntoIterator<Item = Expr>) -> Option<Expr> {
+    // println!("Entering gen_cmp_expr");
+    comparisons.into_iter().reduce(|ord_expr: Expr, expr| {
+        // println!("Combining {} with {}", quote!(#ord_expr), quote!(#expr));
+        let ord_expr = receiver(ord_expr);
+        parse_quote_spanned! {expr.span() =>
+            #ord_expr.then_with(|| #expr)
+        }
+    })
+}
+
+/// Wraps block-like expressions in parentheses, so that methods can be called on them even at the start of a statement.
+pub fn receiver(expr: Expr) -> Expr {
+    match expr {
+        Expr::Match(_) | Expr::Block(_) | Expr::If(_) => parse_quote!((#expr)),
+        expr => expr,
+    }
 }
 
 #[cfg(test)]
 pub(crate) mod test {
+    use crate::parsing::Derive;
     use quote::quote;
 
     #[macro_export]
@@ -204,7 +777,7 @@ pub(crate) mod test {
 impl ::core::cmp::PartialEq<Self> for Toto {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        self.cmp(other).is_eq()
+        self.embed.otherfield == other.embed.otherfield && self.a == other.a && self.c == other.c
     }
 }
 impl ::core::cmp::PartialOrd<Self> for Toto {
@@ -212,6 +785,22 @@ impl ::core::cmp::PartialOrd<Self> for Toto {
     fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
         ::core::option::Option::Some(self.cmp(other))
     }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.cmp(other).is_lt()
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.cmp(other).is_le()
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.cmp(other).is_gt()
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.cmp(other).is_ge()
+    }
 }
 impl ::core::cmp::Ord for Toto {
     #[inline]
@@ -219,8 +808,7 @@ impl ::core::cmp::Ord for Toto {
         self.embed
             .otherfield
             .cmp(&other.embed.otherfield)
-            .then_with(|| self.a.cmp(&other.a)
-            .then_with(|| self.c.cmp(&other.c)))
+            .then_with(|| self.a.cmp(&other.a).then_with(|| self.c.cmp(&other.c)))
     }
 }
 "#
@@ -245,7 +833,16 @@ impl ::core::cmp::Ord for Toto {
 impl ::core::cmp::PartialEq<Self> for Toto {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        self.cmp(other).is_eq()
+        self.this == other.this
+            && self.this.that == other.this.that
+            && self.get_something() == other.get_something()
+            && self.something.do_this() == other.something.do_this()
+            && match (self, other) {
+                (Self::A(this_0, ..), Self::A(other_0, ..)) => this_0 == other_0,
+                (this, other) => {
+                    ::core::mem::discriminant(this) == ::core::mem::discriminant(other)
+                }
+            }
     }
 }
 impl ::core::cmp::PartialOrd<Self> for Toto {
@@ -253,6 +850,22 @@ impl ::core::cmp::PartialOrd<Self> for Toto {
     fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
         ::core::option::Option::Some(self.cmp(other))
     }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.cmp(other).is_lt()
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.cmp(other).is_le()
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.cmp(other).is_gt()
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.cmp(other).is_ge()
+    }
 }
 impl ::core::cmp::Ord for Toto {
     #[inline]
@@ -263,12 +876,12 @@ impl ::core::cmp::Ord for Toto {
             .then_with(|| self.get_something().cmp(&other.get_something()))
             .then_with(|| self.something.do_this().cmp(&other.something.do_this()))
             .then_with(|| match (self, other) {
-                (this @ Self::A(..), other @ Self::A(..)) => self.0.cmp(&other.0),
-                (this, other) => match this {
+                (Self::A(this_0, ..), Self::A(other_0, ..)) => this_0.cmp(other_0),
+                (this, other) => (match this {
                     Self::A(..) => 0,
                     Self::B => 1,
                     Self::G { .. } => 2,
-                }
+                })
                 .cmp(&match other {
                     Self::A(..) => 0,
                     Self::B => 1,
@@ -282,49 +895,40 @@ impl ::core::cmp::Ord for Toto {
     }
 
     #[test]
-    fn test_singlecall() {
-        let input = syn::parse_quote! {
-            #[cmp_by(get_something())]
+    fn test_variant_order() {
+        let input = quote! {
+            #[cmp_by(variant_order = [C, A, B])]
             enum Toto {
-                A(u32),
+                A(#[cmp_by] u32),
                 B,
-                G { doesnotmatter: String, anyway: usize }
+                C { c: u8 }
             }
         };
 
-        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
         assert_rust_eq!(
             output.to_string(),
-            r#"impl ::core::cmp::Eq for Toto {}
-impl ::core::cmp::PartialEq<Self> for Toto {
-    #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        self.cmp(other).is_eq()
-    }
-}
-impl ::core::cmp::PartialOrd<Self> for Toto {
-    #[inline]
-    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
-        ::core::option::Option::Some(self.cmp(other))
-    }
-}
-impl ::core::cmp::Ord for Toto {
+            r#"impl ::core::cmp::Ord for Toto {
     #[inline]
     fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
-        self.get_something()
-            .cmp(&other.get_something())
-            .then_with(|| match (self, other) {
-                (this, other) => match this {
-                    Self::A(..) => 0,
-                    Self::B => 1,
-                    Self::G { .. } => 2,
-                }
-                .cmp(&match other {
-                    Self::A(..) => 0,
-                    Self::B => 1,
-                    Self::G { .. } => 2,
-                }),
+        match (self, other) {
+            (Self::A(this_0, ..), Self::A(other_0, ..)) => this_0.cmp(other_0),
+            (this, other) => (match this {
+                Self::A(..) => 1,
+                Self::B => 2,
+                Self::C { .. } => 0,
             })
+            .cmp(&match other {
+                Self::A(..) => 1,
+                Self::B => 2,
+                Self::C { .. } => 0,
+            }),
+        }
     }
 }
 "#
@@ -332,47 +936,83 @@ impl ::core::cmp::Ord for Toto {
     }
 
     #[test]
-    fn test_lifetime() {
+    fn test_variant_keyword() {
         let input = quote! {
-            #[derive(CmpBy)]
-            pub struct ContextWrapper<'a, T>
-            where T: Ctx,
-            {
-                ctx: Cow<'a, T>,
-                #[cmp_by]
-                elapsed: i32,
+            #[cmp_by(_variant, name())]
+            enum Toto {
+                A(#[cmp_by] u32),
+                B,
             }
         };
 
-        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
         assert_rust_eq!(
             output.to_string(),
-            r#"impl<'a, T> ::core::cmp::Eq for ContextWrapper<'a, T> where T: Ctx {}
-impl<'a, T> ::core::cmp::PartialEq<Self> for ContextWrapper<'a, T>
-where
-    T: Ctx,
-{
+            r#"impl ::core::cmp::Ord for Toto {
     #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        self.cmp(other).is_eq()
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        (match self {
+            Self::A(..) => 0,
+            Self::B => 1,
+        })
+        .cmp(&match other {
+            Self::A(..) => 0,
+            Self::B => 1,
+        })
+        .then_with(|| self.name().cmp(&other.name()))
+        .then_with(|| match (self, other) {
+            (Self::A(this_0, ..), Self::A(other_0, ..)) => this_0.cmp(other_0),
+            _ => ::core::cmp::Ordering::Equal,
+        })
     }
 }
-impl<'a, T> ::core::cmp::PartialOrd<Self> for ContextWrapper<'a, T>
-where
-    T: Ctx,
-{
-    #[inline]
-    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
-        ::core::option::Option::Some(self.cmp(other))
+"#
+        );
     }
-}
-impl<'a, T> ::core::cmp::Ord for ContextWrapper<'a, T>
-where
-    T: Ctx,
-{
+
+    #[test]
+    fn test_discriminant() {
+        let input = quote! {
+            #[cmp_by(discriminant)]
+            #[repr(u8)]
+            enum Status {
+                Control(#[cmp_by] u8) = 0xB0,
+                NoteOff = 0x80,
+                NoteOn,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Ord for Status {
     #[inline]
     fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
-        self.elapsed.cmp(&other.elapsed)
+        match (self, other) {
+            (Self::Control(this_0, ..), Self::Control(other_0, ..)) => this_0.cmp(other_0),
+            (this, other) => <u8 as ::core::cmp::Ord>::cmp(
+                &match this {
+                    Self::Control(..) => 0xB0,
+                    Self::NoteOff => 0x80,
+                    Self::NoteOn => (0x80) + 1,
+                },
+                &match other {
+                    Self::Control(..) => 0xB0,
+                    Self::NoteOff => 0x80,
+                    Self::NoteOn => (0x80) + 1,
+                },
+            ),
+        }
     }
 }
 "#
@@ -380,33 +1020,946 @@ where
     }
 
     #[test]
-    fn test_tuple_struct() {
-        let input = syn::parse_quote! {
-            #[cmp_by(somemethod(), literal, some.path)]
-            struct Something (
-              #[cmp_by]
-              u16,
-              #[cmp_by]
-              u32,
-              f32,
-            );
+    fn test_common() {
+        let input = quote! {
+            #[cmp_by(common(channel), desc(common(pitch)))]
+            enum Message {
+                NoteOn { channel: u8, pitch: u8 },
+                Control { channel: u8, #[cmp_by] value: u8 },
+                Clock,
+            }
         };
 
-        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
         assert_rust_eq!(
             output.to_string(),
-            r#"impl ::core::cmp::Eq for Something {}
-impl ::core::cmp::PartialEq<Self> for Something {
+            r#"impl ::core::cmp::Ord for Message {
     #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        self.cmp(other).is_eq()
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        (match self {
+            Self::NoteOn { channel: value, .. } => ::core::option::Option::Some(value),
+            Self::Control { channel: value, .. } => ::core::option::Option::Some(value),
+            _ => ::core::option::Option::None,
+        })
+        .cmp(&match other {
+            Self::NoteOn { channel: value, .. } => ::core::option::Option::Some(value),
+            Self::Control { channel: value, .. } => ::core::option::Option::Some(value),
+            _ => ::core::option::Option::None,
+        })
+        .then_with(|| {
+            (match self {
+                Self::NoteOn { pitch: value, .. } => ::core::option::Option::Some(value),
+                _ => ::core::option::Option::None,
+            })
+            .cmp(&match other {
+                Self::NoteOn { pitch: value, .. } => ::core::option::Option::Some(value),
+                _ => ::core::option::Option::None,
+            })
+            .reverse()
+        })
+        .then_with(|| match (self, other) {
+            (
+                Self::Control {
+                    value: this_value, ..
+                },
+                Self::Control {
+                    value: other_value, ..
+                },
+            ) => this_value.cmp(other_value),
+            (this, other) => (match this {
+                Self::NoteOn { .. } => 0,
+                Self::Control { .. } => 1,
+                Self::Clock => 2,
+            })
+            .cmp(&match other {
+                Self::NoteOn { .. } => 0,
+                Self::Control { .. } => 1,
+                Self::Clock => 2,
+            }),
+        })
     }
 }
-impl ::core::cmp::PartialOrd<Self> for Something {
-    #[inline]
-    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+"#
+        );
+    }
+
+    #[test]
+    fn test_variant_keys() {
+        let input = quote! {
+            enum Message {
+                #[cmp_by(velocity, desc(pitch))]
+                NoteOn { pitch: u8, velocity: u8 },
+                #[cmp_by(header.seq, _fields, header.id)]
+                Sysex { data: Vec<u8>, #[cmp_by] port: u8, header: Header },
+                #[cmp_by(1)]
+                Control(u8, u8),
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Ord for Message {
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        match (self, other) {
+            (
+                Self::NoteOn {
+                    velocity: this_velocity,
+                    pitch: this_pitch,
+                    ..
+                },
+                Self::NoteOn {
+                    velocity: other_velocity,
+                    pitch: other_pitch,
+                    ..
+                },
+            ) => this_velocity
+                .cmp(other_velocity)
+                .then_with(|| this_pitch.cmp(other_pitch).reverse()),
+            (
+                Self::Sysex {
+                    header: this_header,
+                    port: this_port,
+                    ..
+                },
+                Self::Sysex {
+                    header: other_header,
+                    port: other_port,
+                    ..
+                },
+            ) => this_header
+                .seq
+                .cmp(&other_header.seq)
+                .then_with(|| this_port.cmp(other_port))
+                .then_with(|| this_header.id.cmp(&other_header.id)),
+            (Self::Control(_, this_1, ..), Self::Control(_, other_1, ..)) => this_1.cmp(other_1),
+            (this, other) => (match this {
+                Self::NoteOn { .. } => 0,
+                Self::Sysex { .. } => 1,
+                Self::Control(..) => 2,
+            })
+            .cmp(&match other {
+                Self::NoteOn { .. } => 0,
+                Self::Sysex { .. } => 1,
+                Self::Control(..) => 2,
+            }),
+        }
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_unit_struct() {
+        let input = quote! {
+            struct Marker;
+        };
+
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Ord for Marker {
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        {
+            let _ = other;
+            ::core::cmp::Ordering::Equal
+        }
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_generic_defaults() {
+        let input = quote! {
+            struct Buffer<T: Ord = u8, const N: usize = 4> where [T; N]: Ord {
+                #[cmp_by]
+                data: [T; N],
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl<T: Ord, const N: usize> ::core::cmp::Ord for Buffer<T, N>
+where
+    [T; N]: Ord,
+    T: ::core::cmp::Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        self.data.cmp(&other.data)
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_bound() {
+        let input = quote! {
+            struct Shared<T, U> {
+                #[cmp_by(bound = "T: Ord")]
+                value: Rc<T>,
+                #[cmp_by]
+                other: U,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl<T, U> ::core::cmp::Ord for Shared<T, U>
+where
+    U: ::core::cmp::Ord,
+    T: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        self.value
+            .cmp(&other.value)
+            .then_with(|| self.other.cmp(&other.other))
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_container_bound() {
+        let input = quote! {
+            #[cmp_by((label(), bound = "T: Display"), bound = "U: Ord + Clone")]
+            struct Labelled<T, U> {
+                #[cmp_by]
+                id: U,
+                name: T,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl<T, U> ::core::cmp::Ord for Labelled<T, U>
+where
+    U: Ord + Clone,
+    T: Display,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        self.label()
+            .cmp(&other.label())
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_packed() {
+        let input = syn::parse_quote! {
+            #[cmp_by(header.version, _fields, header.crc())]
+            #[repr(C, packed(2))]
+            struct Frame {
+                header: Header,
+                #[cmp_by]
+                length: u32,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Ord for Frame {
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        #[diagnostic::on_unimplemented(
+            message = "the fields of a `#[repr(packed)]` struct must be `Copy` to be used as keys, `{Self}` is not",
+            label = "cannot be copied out of the packed struct",
+            note = "fields of packed structs may be unaligned and cannot be referenced"
+        )]
+        trait __PackedField {}
+        impl<T: ::core::marker::Copy> __PackedField for T {}
+        fn __packed_field<T: __PackedField>() {}
+        __packed_field::<u32>();
+        __packed_field::<Header>();
+        ({ self.header.version })
+            .cmp(&{ other.header.version })
+            .then_with(|| ({ self.length }).cmp(&{ other.length }))
+            .then_with(|| ({ self.header }).crc().cmp(&({ other.header }).crc()))
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_cmp_key() {
+        let input = syn::parse_quote! {
+            #[cmp_by(cmp_key = "pub(crate)", desc(len()), _fields, (name, key = |name| name.len()))]
+            pub struct Entry<'a, T> {
+                #[cmp_by]
+                id: u32,
+                #[cmp_by(desc)]
+                value: &'a T,
+                name: String,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "OrdBy",
+            &[crate::cmp_by::CmpTrait::Ord],
+        );
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl<'a, T> Entry<'a, T>
+where
+    T: ::core::cmp::Ord,
+{
+    #[doc = r" Keys the values are ordered by, in the order they are compared."]
+    #[inline]
+    pub(crate) fn cmp_key(
+        &self,
+    ) -> (
+        ::core::cmp::Reverse<impl ::core::cmp::Ord + '_>,
+        &u32,
+        ::core::cmp::Reverse<&&'a T>,
+        impl ::core::cmp::Ord + '_,
+    ) {
+        #[inline(always)]
+        fn __project_key<'a, T: ?::core::marker::Sized, K>(
+            value: &'a T,
+            key: impl ::core::ops::FnOnce(&'a T) -> K,
+        ) -> K {
+            key(value)
+        }
+        (
+            ::core::cmp::Reverse(self.len()),
+            &self.id,
+            ::core::cmp::Reverse(&self.value),
+            __project_key(&self.name, |name| name.len()),
+        )
+    }
+}
+impl<'a, T> ::core::cmp::Ord for Entry<'a, T>
+where
+    T: ::core::cmp::Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        ::core::cmp::Ord::cmp(&self.cmp_key(), &other.cmp_key())
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_singlecall() {
+        let input = syn::parse_quote! {
+            #[cmp_by(get_something())]
+            enum Toto {
+                A(u32),
+                B,
+                G { doesnotmatter: String, anyway: usize }
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Eq for Toto {}
+impl ::core::cmp::PartialEq<Self> for Toto {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.get_something() == other.get_something()
+            && match (self, other) {
+                (this, other) => {
+                    ::core::mem::discriminant(this) == ::core::mem::discriminant(other)
+                }
+            }
+    }
+}
+impl ::core::cmp::PartialOrd<Self> for Toto {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+        ::core::option::Option::Some(self.cmp(other))
+    }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.cmp(other).is_lt()
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.cmp(other).is_le()
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.cmp(other).is_gt()
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.cmp(other).is_ge()
+    }
+}
+impl ::core::cmp::Ord for Toto {
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        self.get_something()
+            .cmp(&other.get_something())
+            .then_with(|| match (self, other) {
+                (this, other) => (match this {
+                    Self::A(..) => 0,
+                    Self::B => 1,
+                    Self::G { .. } => 2,
+                })
+                .cmp(&match other {
+                    Self::A(..) => 0,
+                    Self::B => 1,
+                    Self::G { .. } => 2,
+                }),
+            })
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_direction() {
+        let input = syn::parse_quote! {
+            #[cmp_by(desc(score()), name)]
+            struct Player {
+                #[cmp_by(desc)]
+                level: u8,
+                #[cmp_by(asc)]
+                id: u32,
+                name: String,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Eq for Player {}
+impl ::core::cmp::PartialEq<Self> for Player {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.score() == other.score()
+            && self.name == other.name
+            && self.level == other.level
+            && self.id == other.id
+    }
+}
+impl ::core::cmp::PartialOrd<Self> for Player {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+        ::core::option::Option::Some(self.cmp(other))
+    }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.cmp(other).is_lt()
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.cmp(other).is_le()
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.cmp(other).is_gt()
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.cmp(other).is_ge()
+    }
+}
+impl ::core::cmp::Ord for Player {
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        self.score()
+            .cmp(&other.score())
+            .reverse()
+            .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| {
+                self.level
+                    .cmp(&other.level)
+                    .reverse()
+                    .then_with(|| self.id.cmp(&other.id))
+            })
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_with() {
+        let input = syn::parse_quote! {
+            #[cmp_by((name(), with = cmp_ci), desc(score(), with = scores::cmp))]
+            enum Entry {
+                A(#[cmp_by(with = f32::total_cmp)] f32),
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Eq for Entry {}
+impl ::core::cmp::PartialEq<Self> for Entry {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        cmp_ci(&self.name(), &other.name()).is_eq()
+            && scores::cmp(&self.score(), &other.score()).is_eq()
+            && match (self, other) {
+                (Self::A(this_0, ..), Self::A(other_0, ..)) => {
+                    f32::total_cmp(this_0, other_0).is_eq()
+                }
+                (this, other) => {
+                    ::core::mem::discriminant(this) == ::core::mem::discriminant(other)
+                }
+            }
+    }
+}
+impl ::core::cmp::PartialOrd<Self> for Entry {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+        ::core::option::Option::Some(self.cmp(other))
+    }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.cmp(other).is_lt()
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.cmp(other).is_le()
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.cmp(other).is_gt()
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.cmp(other).is_ge()
+    }
+}
+impl ::core::cmp::Ord for Entry {
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        cmp_ci(&self.name(), &other.name())
+            .then_with(|| scores::cmp(&self.score(), &other.score()).reverse())
+            .then_with(|| match (self, other) {
+                (Self::A(this_0, ..), Self::A(other_0, ..)) => f32::total_cmp(this_0, other_0),
+                (this, other) => (match this {
+                    Self::A(..) => 0,
+                })
+                .cmp(&match other {
+                    Self::A(..) => 0,
+                }),
+            })
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_key() {
+        let input = syn::parse_quote! {
+            #[cmp_by(key = |s| s.name.len(), (name, key = str::len))]
+            struct Person {
+                name: String,
+                #[cmp_by(desc, key = |n| n.abs())]
+                age: i32,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Eq for Person {}
+impl ::core::cmp::PartialEq<Self> for Person {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        #[inline(always)]
+        fn __project_key<'a, T: ?::core::marker::Sized, K>(
+            value: &'a T,
+            key: impl ::core::ops::FnOnce(&'a T) -> K,
+        ) -> K {
+            key(value)
+        }
+        __project_key(self, |s| s.name.len()) == __project_key(other, |s| s.name.len())
+            && (str::len)(&self.name) == (str::len)(&other.name)
+            && __project_key(&self.age, |n| n.abs()) == __project_key(&other.age, |n| n.abs())
+    }
+}
+impl ::core::cmp::PartialOrd<Self> for Person {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+        ::core::option::Option::Some(self.cmp(other))
+    }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.cmp(other).is_lt()
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.cmp(other).is_le()
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.cmp(other).is_gt()
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.cmp(other).is_ge()
+    }
+}
+impl ::core::cmp::Ord for Person {
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        #[inline(always)]
+        fn __project_key<'a, T: ?::core::marker::Sized, K>(
+            value: &'a T,
+            key: impl ::core::ops::FnOnce(&'a T) -> K,
+        ) -> K {
+            key(value)
+        }
+        __project_key(self, |s| s.name.len())
+            .cmp(&__project_key(other, |s| s.name.len()))
+            .then_with(|| (str::len)(&self.name).cmp(&(str::len)(&other.name)))
+            .then_with(|| {
+                __project_key(&self.age, |n| n.abs())
+                    .cmp(&__project_key(&other.age, |n| n.abs()))
+                    .reverse()
+            })
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_float() {
+        let input = syn::parse_quote! {
+            #[cmp_by(desc(nan_last(ratio())))]
+            struct Sample {
+                #[cmp_by(total)]
+                a: f32,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Eq for Sample {}
+impl ::core::cmp::PartialEq<Self> for Sample {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        (match (&self.ratio(), &other.ratio()) {
+            (this, other) => this == other || this.is_nan() && other.is_nan(),
+        }) && self.a.total_cmp(&other.a).is_eq()
+    }
+}
+impl ::core::cmp::PartialOrd<Self> for Sample {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+        ::core::option::Option::Some(self.cmp(other))
+    }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.cmp(other).is_lt()
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.cmp(other).is_le()
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.cmp(other).is_gt()
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.cmp(other).is_ge()
+    }
+}
+impl ::core::cmp::Ord for Sample {
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        (match (&self.ratio(), &other.ratio()) {
+            (this, other) => this.is_nan().cmp(&other.is_nan()).then_with(|| {
+                this.partial_cmp(other)
+                    .unwrap_or(::core::cmp::Ordering::Equal)
+                    .reverse()
+            }),
+        })
+        .then_with(|| self.a.total_cmp(&other.a))
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_none() {
+        let input = syn::parse_quote! {
+            struct Report {
+                #[cmp_by(desc, none_last)]
+                score: Option<u32>,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Eq for Report {}
+impl ::core::cmp::PartialEq<Self> for Report {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.score == other.score
+    }
+}
+impl ::core::cmp::PartialOrd<Self> for Report {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+        ::core::option::Option::Some(self.cmp(other))
+    }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.cmp(other).is_lt()
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.cmp(other).is_le()
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.cmp(other).is_gt()
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.cmp(other).is_ge()
+    }
+}
+impl ::core::cmp::Ord for Report {
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        match (&self.score, &other.score) {
+            (::core::option::Option::Some(this), ::core::option::Option::Some(other)) => {
+                this.cmp(other).reverse()
+            }
+            (::core::option::Option::None, ::core::option::Option::None) => {
+                ::core::cmp::Ordering::Equal
+            }
+            (::core::option::Option::None, ::core::option::Option::Some(_)) => {
+                ::core::cmp::Ordering::Greater
+            }
+            (::core::option::Option::Some(_), ::core::option::Option::None) => {
+                ::core::cmp::Ordering::Less
+            }
+        }
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_standalone_partial_ord() {
+        let input = quote! {
+            struct Something {
+                #[cmp_by(desc)]
+                a: u16,
+                b: f32,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_traits(
+            syn::parse2(input).unwrap(),
+            Derive::CmpBy,
+            "PartialOrdBy",
+            &[crate::cmp_by::CmpTrait::PartialOrd],
+        );
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::PartialOrd<Self> for Something {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+        ::core::option::Option::Some(self.a.cmp(&other.a).reverse())
+    }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        other.a < self.a
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        other.a <= self.a
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        other.a > self.a
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        other.a >= self.a
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_lifetime() {
+        let input = quote! {
+            #[derive(CmpBy)]
+            pub struct ContextWrapper<'a, T>
+            where T: Ctx,
+            {
+                ctx: Cow<'a, T>,
+                #[cmp_by]
+                elapsed: i32,
+            }
+        };
+
+        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl<'a, T> ::core::cmp::Eq for ContextWrapper<'a, T> where T: Ctx {}
+impl<'a, T> ::core::cmp::PartialEq<Self> for ContextWrapper<'a, T>
+where
+    T: Ctx,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.elapsed == other.elapsed
+    }
+}
+impl<'a, T> ::core::cmp::PartialOrd<Self> for ContextWrapper<'a, T>
+where
+    T: Ctx,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
         ::core::option::Option::Some(self.cmp(other))
     }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.elapsed < other.elapsed
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.elapsed <= other.elapsed
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.elapsed > other.elapsed
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.elapsed >= other.elapsed
+    }
+}
+impl<'a, T> ::core::cmp::Ord for ContextWrapper<'a, T>
+where
+    T: Ctx,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        self.elapsed.cmp(&other.elapsed)
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_tuple_struct() {
+        let input = syn::parse_quote! {
+            #[cmp_by(somemethod(), literal, some.path)]
+            struct Something (
+              #[cmp_by]
+              u16,
+              #[cmp_by]
+              u32,
+              f32,
+            );
+        };
+
+        let output = crate::cmp_by::impl_cmp_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Eq for Something {}
+impl ::core::cmp::PartialEq<Self> for Something {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.somemethod() == other.somemethod()
+            && self.literal == other.literal
+            && self.some.path == other.some.path
+            && self.0 == other.0
+            && self.1 == other.1
+    }
+}
+impl ::core::cmp::PartialOrd<Self> for Something {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+        ::core::option::Option::Some(self.cmp(other))
+    }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.cmp(other).is_lt()
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.cmp(other).is_le()
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.cmp(other).is_gt()
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.cmp(other).is_ge()
+    }
 }
 impl ::core::cmp::Ord for Something {
     #[inline]
@@ -415,8 +1968,7 @@ impl ::core::cmp::Ord for Something {
             .cmp(&other.somemethod())
             .then_with(|| self.literal.cmp(&other.literal))
             .then_with(|| self.some.path.cmp(&other.some.path))
-            .then_with(|| self.0.cmp(&other.0)
-            .then_with(|| self.1.cmp(&other.1)))
+            .then_with(|| self.0.cmp(&other.0).then_with(|| self.1.cmp(&other.1)))
     }
 }
 "#
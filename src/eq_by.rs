@@ -0,0 +1,274 @@
+use crate::cmp_by::receiver;
+use crate::parsing::{
+    bounded_generics, parse_input, Derive, FloatOrder, Item, Key, KeyOptions, Operand,
+    ParsedFields, ParsedInput, ParsingError,
+};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{parse_quote_spanned, spanned::Spanned, DeriveInput, Error, Expr};
+
+pub fn impl_eq_by_derive(input: DeriveInput) -> TokenStream {
+    let input_span = input.span();
+    let struct_name = input.ident.clone();
+
+    let parsed = match parse_input(input, Derive::EqBy) {
+        Ok(value) => value,
+        Err(err) => {
+            return match err {
+                ParsingError::Error(err) => err,
+                ParsingError::NoField(span) => Error::new(
+                    span,
+                    "EqBy: no field to compare on. Mark fields to compare on with #[eq_by]",
+                ),
+            }
+            .into_compile_error()
+        }
+    };
+    let prelude = parsed.prelude();
+    let ParsedInput {
+        expressions: eq_expressions,
+        fields: eq_fields,
+        generics,
+        discriminant_type: _,
+        bounded_params,
+        bounds,
+        packed,
+        allow_inconsistent_hash: _,
+        cmp_key: _,
+        members: _,
+    } = parsed;
+
+    let eq_expression = gen_eq_expression(
+        &eq_expressions,
+        &eq_fields,
+        packed.is_some(),
+        input_span,
+        gen_eq,
+    );
+
+    let eq_generics =
+        bounded_generics(&generics, &bounded_params, &bounds, quote!(::core::cmp::Eq));
+    let (impl_generics, ty_generics, where_clause) = eq_generics.split_for_impl();
+    let eq_impl = quote_spanned! {input_span =>
+        impl #impl_generics ::core::cmp::Eq for #struct_name #ty_generics #where_clause {}
+    };
+    let partial_eq_generics = bounded_generics(
+        &generics,
+        &bounded_params,
+        &bounds,
+        quote!(::core::cmp::PartialEq),
+    );
+    let (impl_generics, ty_generics, where_clause) = partial_eq_generics.split_for_impl();
+
+    quote_spanned! {input_span =>
+        #eq_impl
+        impl #impl_generics ::core::cmp::PartialEq<Self> for #struct_name #ty_generics #where_clause {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                #prelude
+                #eq_expression
+            }
+        }
+    }
+}
+
+/// Tests the container-level items and the marked fields for equality, in order, with `gen_eq`.
+pub fn gen_eq_expression(
+    expressions: &[Key<Item>],
+    fields: &ParsedFields,
+    packed: bool,
+    input_span: Span,
+    gen_eq: impl Fn(Operand, Operand, &KeyOptions) -> Expr,
+) -> Expr {
+    let variant_placed = expressions.iter().any(|key| key.value.is_variant_keyword());
+    let field_eq_statement = match fields {
+        ParsedFields::Struct(fields) => gen_eq_exprs(fields.iter().map(
+            |Key {
+                 value: member,
+                 options,
+             }| {
+                gen_eq(
+                    Operand::field(quote!(self), member, packed),
+                    Operand::field(quote!(other), member, packed),
+                    options,
+                )
+            },
+        )),
+        // Enums without variants have no values to compare.
+        fields if fields.is_empty_enum() => Some(parse_quote_spanned! {input_span => {
+            let _ = other;
+            match *self {}
+        }}),
+        ParsedFields::Enum(variants) => {
+            let eq_statements = variants
+                .iter()
+                .filter(|variant| !variant.fields.is_empty())
+                .map(|variant| {
+                    let this_pattern = variant.binding_pattern("this");
+                    let other_pattern = variant.binding_pattern("other");
+                    let eq_statement = gen_eq_exprs(variant.fields.iter().map(
+                        |Key {
+                             value: path,
+                             options,
+                         }| {
+                            gen_eq(
+                                Operand::binding("this", path),
+                                Operand::binding("other", path),
+                                options,
+                            )
+                        },
+                    ));
+                    quote_spanned! {variant.ident.span() => (#this_pattern, #other_pattern) => #eq_statement}
+                });
+            // Values of different variants are never equal, and values of the same variant without
+            // marked fields always are. When `_variant` places that test among the container-level
+            // items, it has already been done or will be.
+            let variant_eq = if variant_placed {
+                quote!(_ => true)
+            } else {
+                quote!((this, other) => ::core::mem::discriminant(this) == ::core::mem::discriminant(other))
+            };
+            Some(parse_quote_spanned! { input_span =>
+                match (self, other) {
+                    #(#eq_statements,)*
+                    #variant_eq,
+                }
+            })
+        }
+    };
+
+    let expr_eq_statements = gen_eq_exprs(expressions.iter().filter_map(
+        |Key {
+             value: expr,
+             options,
+         }| {
+            if expr.is_fields_keyword() {
+                return field_eq_statement.clone();
+            }
+            if expr.is_variant_keyword() {
+                return Some(parse_quote_spanned! { input_span =>
+                    ::core::mem::discriminant(self) == ::core::mem::discriminant(other)
+                });
+            }
+            Some(gen_eq(
+                Operand::item(quote!(self), expr, packed),
+                Operand::item(quote!(other), expr, packed),
+                options,
+            ))
+        },
+    ));
+
+    // Fields placed among the container-level items with `_fields` are not tested again.
+    let fields_placed = expressions.iter().any(|key| key.value.is_fields_keyword());
+    let field_eq_statement = field_eq_statement.filter(|_| !fields_placed);
+    match (expr_eq_statements, field_eq_statement) {
+        (Some(exprs), Some(fields)) => gen_eq_exprs([exprs, fields]).expect("Two expressions."),
+        (None, Some(expr)) | (Some(expr), None) => expr,
+        // Values of unit structs are all equal.
+        (None, None) => parse_quote_spanned! {input_span => {
+            let _ = other;
+            true
+        }},
+    }
+}
+
+/// Tests `this` and `other` for equality according to the key's options.
+fn gen_eq(this: Operand, other: Operand, options: &KeyOptions) -> Expr {
+    let span = this.receiver.span();
+    let (this, other) = (this.project(options), other.project(options));
+    if let Some(with) = &options.with {
+        let (this, other) = (this.reference, other.reference);
+        parse_quote_spanned! { span =>
+            #with(#this, #other)
+        }
+    } else if options.float == Some(FloatOrder::Total) {
+        // Like CmpBy's `total`, which makes the equality reflexive. HashBy hashes the values equal this way alike.
+        let (this, other) = (this.receiver, other.reference);
+        parse_quote_spanned! { span =>
+            #this.total_cmp(#other).is_eq()
+        }
+    } else {
+        let (this, other) = (this.receiver, other.receiver);
+        parse_quote_spanned! { span =>
+            #this == #other
+        }
+    }
+}
+
+/// Chains the equality tests in order, each one only being evaluated when the previous ones hold.
+fn gen_eq_exprs(tests: impl IntoIterator<Item = Expr>) -> Option<Expr> {
+    tests.into_iter().reduce(|eq_expr: Expr, expr| {
+        let eq_expr = receiver(eq_expr);
+        parse_quote_spanned! {expr.span() =>
+            #eq_expr && #expr
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assert_rust_eq;
+
+    #[test]
+    fn test_struct() {
+        let input = syn::parse_quote! {
+            #[eq_by(embed.otherfield, name())]
+            struct Toto {
+                #[eq_by]
+                a: u16,
+                #[eq_by(total)]
+                c: f32,
+                b: f32,
+                embed: EmbedStruct
+            }
+        };
+
+        let output = impl_eq_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Eq for Toto {}
+impl ::core::cmp::PartialEq<Self> for Toto {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.embed.otherfield == other.embed.otherfield
+            && self.name() == other.name()
+            && self.a == other.a
+            && self.c.total_cmp(&other.c).is_eq()
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_enum() {
+        let input = syn::parse_quote! {
+            #[eq_by(_fields, (name(), with = eq_ci))]
+            enum Toto {
+                A(#[eq_by] u32, f32),
+                B,
+                G { doesnotmatter: String, #[eq_by(key = str::len)] anyway: String }
+            }
+        };
+
+        let output = impl_eq_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Eq for Toto {}
+impl ::core::cmp::PartialEq<Self> for Toto {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        (match (self, other) {
+            (Self::A(this_0, ..), Self::A(other_0, ..)) => this_0 == other_0,
+            (Self::G { anyway: this_anyway, .. }, Self::G { anyway: other_anyway, .. }) => {
+                (str::len)(this_anyway) == (str::len)(other_anyway)
+            }
+            (this, other) => ::core::mem::discriminant(this) == ::core::mem::discriminant(other),
+        }) && eq_ci(&self.name(), &other.name())
+    }
+}
+"#
+        );
+    }
+}
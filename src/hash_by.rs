@@ -1,36 +1,71 @@
-use crate::parsing::{parse_input, ParsedFields, ParsedInput, ParsingError};
-use proc_macro2::{Literal, TokenStream};
+use crate::cmp_by::parse_keys;
+use crate::parsing::{
+    bounded_generics, parse_input, Derive, Key, KeyOptions, Operand, ParsedFields, ParsedInput,
+    ParsedVariant,
+};
+use proc_macro2::{Literal, Span, TokenStream};
 use quote::{quote, quote_spanned, ToTokens};
 use syn::spanned::Spanned;
-use syn::{DeriveInput, Error};
+use syn::{Attribute, Data, DeriveInput, Error, Ident};
 
+/// Implements `Hash` from the `#[hash_by]` keys, checked against the equality derived from other attributes.
 pub fn impl_hash_by_derive(input: DeriveInput) -> TokenStream {
     let input_span = input.span();
     let struct_name = input.ident.clone();
+    let equality = equality_keys(&input);
 
+    let parsed = match parse_keys(input, Derive::HashBy, "HashBy") {
+        Ok(parsed) => parsed,
+        Err(err) => return err.into_compile_error(),
+    };
+    if let Some((derive, equality)) = equality.filter(|_| !parsed.allow_inconsistent_hash) {
+        if let Err(err) = check_consistency(&parsed, &equality, derive) {
+            return err.into_compile_error();
+        }
+    }
+    gen_hash_impl(&parsed, &struct_name, input_span)
+}
+
+/// Implements `Hash` for `struct_name` from its parsed keys.
+pub fn gen_hash_impl(parsed: &ParsedInput, struct_name: &Ident, input_span: Span) -> TokenStream {
+    let prelude = parsed.prelude();
     let ParsedInput {
         expressions: sortable_expressions,
         fields: sortable_fields,
         generics,
-        generic_arguments: generics_params,
-    } = match parse_input(input, "hash_by") {
-        Ok(value) => value,
-        Err(err) => {
-            return match err {
-                ParsingError::Error(err) => err,
-                ParsingError::NoField(span) => Error::new(
-                    span,
-                    "HashBy: no field to compare on. Mark fields to compare on with #[hash_by]",
-                ),
-            }
-            .into_compile_error()
-        }
-    };
+        discriminant_type,
+        bounded_params,
+        bounds,
+        packed,
+        allow_inconsistent_hash: _,
+        cmp_key: _,
+        members: _,
+    } = parsed;
+    let packed = packed.is_some();
+
+    let variant_placed = sortable_expressions
+        .iter()
+        .any(|key| key.value.is_variant_keyword());
 
     let expr_hash_statements = {
+        // Fields placed by KeyBy's `_fields` only change the order of the comparisons.
         let mut hash_exprs = sortable_expressions
             .iter()
-            .map(|expr| quote_spanned!(expr.span() => self.#expr.hash(state)))
+            .filter(|key| !key.value.is_fields_keyword())
+            .map(
+                |Key {
+                     value: item,
+                     options,
+                 }| {
+                    if let (true, ParsedFields::Enum(variants)) =
+                        (item.is_variant_keyword(), sortable_fields)
+                    {
+                        gen_variant_hash(variants, discriminant_type.as_ref(), input_span)
+                    } else {
+                        gen_hash(Operand::item(quote!(self), item, packed), options)
+                    }
+                },
+            )
             .peekable();
         if hash_exprs.peek().is_some() {
             Some(quote!(#(#hash_exprs);*;))
@@ -39,11 +74,18 @@ pub fn impl_hash_by_derive(input: DeriveInput) -> TokenStream {
         }
     };
 
-    let field_hash_expressions = match &sortable_fields {
+    let field_hash_expressions = match sortable_fields {
         ParsedFields::Struct(sortable_expr) => {
             let mut hash_exprs = sortable_expr
                 .iter()
-                .map(|expr| quote_spanned!(expr.span() => self.#expr.hash(state)))
+                .map(
+                    |Key {
+                         value: member,
+                         options,
+                     }| {
+                        gen_hash(Operand::field(quote!(self), member, packed), options)
+                    },
+                )
                 .peekable();
             if hash_exprs.peek().is_some() {
                 Some(quote! { #(#hash_exprs);*; })
@@ -51,21 +93,45 @@ pub fn impl_hash_by_derive(input: DeriveInput) -> TokenStream {
                 None
             }
         }
+        // Enums without variants have no values to hash.
+        fields if fields.is_empty_enum() => Some(quote! {
+            let _ = state;
+            match *self {}
+        }),
+        ParsedFields::Enum(sortable_variants)
+            if variant_placed && sortable_variants.iter().all(|v| v.fields.is_empty()) =>
+        {
+            None
+        }
         ParsedFields::Enum(sortable_variants) => {
             let mut hash_statements = sortable_variants
                 .iter()
                 .enumerate()
-                .filter(|(_, (_, sortable_expr))| !sortable_expr.is_empty())
-                .map(|(i, (variant, sortable_expr))| {
-                    let hash_pattern = quote_spanned! {variant.span() => this @ #variant};
-                    let variant_num = Literal::usize_unsuffixed(i).to_token_stream();
-                    let variant_hash_statement = quote! {state.write_u8(#variant_num)};
-                    let hash_statement = sortable_expr
-                        .iter()
-                        .map(|expr| quote_spanned!(expr.span() => self.#expr.hash(state)));
+                .map(|(i, variant)| {
+                    // Every variant hashes its discriminant, so that values of different variants
+                    // hash differently just like CmpBy orders them by variant index, unless
+                    // `_variant` hashes it among the container-level items.
+                    let hash_pattern = variant.binding_pattern("this");
+                    let variant_hash_statement = (!variant_placed).then(|| {
+                        if let Some(discriminant_type) = &discriminant_type {
+                            let rank = &variant.rank;
+                            quote! {<#discriminant_type as ::core::hash::Hash>::hash(&(#rank), state);}
+                        } else {
+                            let variant_num = Literal::usize_unsuffixed(i).to_token_stream();
+                            quote! {state.write_usize(#variant_num);}
+                        }
+                    });
+                    let hash_statement = variant.fields.iter().map(
+                        |Key {
+                             value: path,
+                             options,
+                         }| {
+                            gen_hash(Operand::binding("this", path), options)
+                        },
+                    );
                     quote! {
                         #hash_pattern => {
-                            #variant_hash_statement;
+                            #variant_hash_statement
                             #(#hash_statement);*
                         }
                     }
@@ -91,14 +157,17 @@ pub fn impl_hash_by_derive(input: DeriveInput) -> TokenStream {
             }
         }
         (Some(stmts), None) | (None, Some(stmts)) => stmts,
-        (None, None) => unreachable!("Missing fields to hash by should have errored earlier."),
+        // Values of unit structs are all equal, so there is nothing to hash.
+        (None, None) => quote!(let _ = state;),
     };
 
-    let where_clause = &generics.where_clause;
+    let generics = bounded_generics(generics, bounded_params, bounds, quote!(::core::hash::Hash));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     quote_spanned! {input_span =>
-        impl #generics ::core::hash::Hash for #struct_name <#(#generics_params),*> #where_clause {
+        impl #impl_generics ::core::hash::Hash for #struct_name #ty_generics #where_clause {
             fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                #prelude
                 #hash_expr
             }
         }
@@ -106,6 +175,140 @@ pub fn impl_hash_by_derive(input: DeriveInput) -> TokenStream {
     }
 }
 
+/// Keys tested for equality by the derive whose attributes are found on the type: EqBy's, or else CmpBy's.
+///
+/// `#[cmp_by]` attributes are assumed to give the keys of `PartialEq`, derived by CmpBy or PartialEqBy. A derive cannot
+/// see the other derives of the type, so when they only serve PartialOrdBy and OrdBy, along with another `PartialEq`,
+/// the check is wrong and `allow_inconsistent_hash` turns it off.
+///
+/// Invalid attributes are left for that derive to report.
+fn equality_keys(input: &DeriveInput) -> Option<(Derive, ParsedInput)> {
+    [Derive::EqBy, Derive::CmpBy]
+        .into_iter()
+        .find(|derive| has_attr(input, derive.attr()))
+        .and_then(|derive| Some((derive, parse_input(input.clone(), derive).ok()?)))
+}
+
+/// Whether the type, one of its variants or one of its fields has an `attr` attribute.
+fn has_attr(input: &DeriveInput, attr: &str) -> bool {
+    let has = |attrs: &[Attribute]| attrs.iter().any(|a| a.path().is_ident(attr));
+    has(&input.attrs)
+        || match &input.data {
+            Data::Struct(data) => data.fields.iter().any(|field| has(&field.attrs)),
+            Data::Enum(data) => data.variants.iter().any(|variant| {
+                has(&variant.attrs) || variant.fields.iter().any(|field| has(&field.attrs))
+            }),
+            Data::Union(_) => false,
+        }
+}
+
+/// Rejects the hashed fields that the equality derived by `derive` doesn't test, since equal values must hash the same.
+///
+/// A key tested with a `key` projection or a `with` function is only consistent with a hashed key sharing them.
+fn check_consistency(
+    hashed: &ParsedInput,
+    equality: &ParsedInput,
+    derive: Derive,
+) -> syn::Result<()> {
+    let key =
+        |options: &KeyOptions| (options.key.as_ref()).map(|key| key.to_token_stream().to_string());
+    let consistent = |hash: &KeyOptions, eq: &KeyOptions| {
+        (eq.with.is_none() || hash.with.is_some())
+            && (eq.key.is_none() || hash.with.is_some() || key(eq) == key(hash))
+    };
+    let eq_keys = equality.key_names();
+    hashed
+        .key_names()
+        .into_iter()
+        // Values computed from the fields, like method calls, may only depend on tested fields, which can't be verified.
+        .filter(|hash| hash.is_field)
+        .filter(|hash| {
+            !eq_keys.iter().any(|eq| {
+                eq.variant == hash.variant && eq.name == hash.name && consistent(hash.options, eq.options)
+            })
+        })
+        .map(|hash| {
+            // Unlike `#[eq_by]`, `#[cmp_by]` is also read by derives that leave `PartialEq` out.
+            let assumption = if derive == Derive::CmpBy {
+                " (this assumes `PartialEq` is derived from the `#[cmp_by]` keys, which cannot be verified: allow it \
+                 as well when they only serve `PartialOrdBy` and `OrdBy`)"
+            } else {
+                ""
+            };
+            Error::new(
+                hash.span,
+                format!(
+                    "this key is hashed but not tested for equality the same way by `#[{}]`, so equal values \
+                     could hash differently; add it to the equality keys, or allow it with \
+                     `#[hash_by(allow_inconsistent_hash)]`{assumption}",
+                    derive.attr()
+                ),
+            )
+        })
+        .reduce(|mut error, err| {
+            error.combine(err);
+            error
+        })
+        .map_or(Ok(()), Err)
+}
+
+/// Feeds the variant of `self` into `state`, as its discriminant value when `discriminant_type` is given.
+fn gen_variant_hash(
+    variants: &[ParsedVariant],
+    discriminant_type: Option<&Ident>,
+    span: Span,
+) -> TokenStream {
+    if variants.is_empty() {
+        return quote_spanned!(span => match *self {});
+    }
+    let Some(discriminant_type) = discriminant_type else {
+        return quote_spanned!(span => ::core::mem::discriminant(self).hash(state));
+    };
+    let idx_statements = variants.iter().map(|variant| {
+        let pattern = variant.pattern();
+        let rank = &variant.rank;
+        quote! {#pattern => #rank}
+    });
+    quote_spanned! { span =>
+        <#discriminant_type as ::core::hash::Hash>::hash(
+            &match self {
+                #(#idx_statements,)*
+            },
+            state,
+        )
+    }
+}
+
+/// Feeds the key into `state`, according to the key's options.
+fn gen_hash(operand: Operand, options: &KeyOptions) -> TokenStream {
+    let span = operand.receiver.span();
+    let operand = operand.project(options);
+    if let Some(with) = &options.with {
+        let reference = operand.reference;
+        quote_spanned!(span => #with(#reference, state))
+    } else if options.float.is_some() {
+        // Adding zero turns `-0.0` into `0.0`, and NaNs all hash as `None`, so that values equal
+        // for any float ordering of CmpBy hash the same.
+        let reference = operand.reference;
+        let float_hash = quote!((!value.is_nan()).then(|| (*value + 0.0).to_bits()));
+        if options.none.is_some() {
+            // KeyBy's optional floats, which HashBy doesn't support.
+            quote_spanned! { span =>
+                ::core::option::Option::as_ref(#reference).map(|value| #float_hash).hash(state)
+            }
+        } else {
+            quote_spanned! { span =>
+                match #reference {
+                    value => #float_hash.hash(state),
+                }
+            }
+        }
+    } else {
+        let receiver = operand.receiver;
+        quote_spanned!(span => #receiver.hash(state))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -160,9 +363,78 @@ mod test {
         self.get_something().hash(state);
         self.something.do_this().hash(state);
         match self {
-            this @ Self::A(..) => {
-                state.write_u8(0);
-                self.0.hash(state)
+            Self::A(this_0, ..) => {
+                state.write_usize(0);
+                this_0.hash(state)
+            }
+            Self::B => {
+                state.write_usize(1);
+            }
+            Self::G { .. } => {
+                state.write_usize(2);
+            }
+        }
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_variant_keyword() {
+        let input = syn::parse_quote! {
+            #[hash_by(_variant, name())]
+            enum Toto {
+                A(#[hash_by] u32),
+                B,
+            }
+        };
+
+        let output = impl_hash_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::hash::Hash for Toto {
+    fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+        ::core::mem::discriminant(self).hash(state);
+        self.name().hash(state);
+        match self {
+            Self::A(this_0, ..) => {
+                this_0.hash(state)
+            }
+            Self::B => {}
+        }
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_common() {
+        let input = syn::parse_quote! {
+            #[hash_by(common(0, required))]
+            enum Owner {
+                User(u32),
+                Group(u32, String),
+            }
+        };
+
+        let output = impl_hash_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::hash::Hash for Owner {
+    fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+        (*match self {
+            Self::User(value, ..) => value,
+            Self::Group(value, ..) => value,
+        })
+        .hash(state);
+        match self {
+            Self::User(..) => {
+                state.write_usize(0);
+            }
+            Self::Group(..) => {
+                state.write_usize(1);
             }
         }
     }
@@ -171,6 +443,63 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_empty_enum() {
+        let input = syn::parse_quote! {
+            enum Never {}
+        };
+
+        let output = impl_hash_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::hash::Hash for Never {
+    fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+        let _ = state;
+        match *self {}
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_inferred_bounds() {
+        let input = syn::parse_quote! {
+            struct Wrapper<'a, T, U, V, I: Iterator> {
+                #[hash_by]
+                key: &'a T,
+                #[hash_by]
+                next: Option<I::Item>,
+                #[hash_by]
+                marker: PhantomData<U>,
+                #[hash_by(with = hash_v)]
+                custom: V,
+                #[hash_by]
+                count: usize,
+                payload: Vec<T>,
+            }
+        };
+
+        let output = impl_hash_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl<'a, T, U, V, I: Iterator> ::core::hash::Hash for Wrapper<'a, T, U, V, I>
+where
+    T: ::core::hash::Hash,
+    I::Item: ::core::hash::Hash,
+{
+    fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+        self.next.hash(state);
+        self.marker.hash(state);
+        hash_v(&self.custom, state);
+        self.count.hash(state);
+    }
+}
+"#
+        );
+    }
+
     #[test]
     fn test_singlecall() {
         let input = syn::parse_quote! {
@@ -188,6 +517,65 @@ mod test {
             r#"impl ::core::hash::Hash for Toto {
     fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
         self.get_something().hash(state);
+        match self {
+            Self::A(..) => {
+                state.write_usize(0);
+            }
+            Self::B => {
+                state.write_usize(1);
+            }
+            Self::G { .. } => {
+                state.write_usize(2);
+            }
+        }
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_with() {
+        let input = syn::parse_quote! {
+            #[hash_by((name(), with = hash_ci))]
+            struct Entry {
+                #[hash_by(with = hash_bits)]
+                value: f32,
+            }
+        };
+
+        let output = impl_hash_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::hash::Hash for Entry {
+    fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+        hash_ci(&self.name(), state);
+        hash_bits(&self.value, state);
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_total() {
+        let input = syn::parse_quote! {
+            struct Sample {
+                #[hash_by(total)]
+                a: f32,
+            }
+        };
+
+        let output = impl_hash_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::hash::Hash for Sample {
+    fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+        match &self.a {
+            value => (!value.is_nan())
+                .then(|| (*value + 0.0).to_bits())
+                .hash(state),
+        };
     }
 }
 "#
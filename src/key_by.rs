@@ -0,0 +1,132 @@
+use crate::cmp_by::{gen_cmp_traits, parse_keys, CmpTrait};
+use crate::hash_by::gen_hash_impl;
+use crate::parsing::Derive;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{spanned::Spanned, DeriveInput};
+
+/// Implements the comparison traits and `Hash` from the same `#[by]` keys, which keeps them consistent.
+pub fn impl_key_by_derive(input: DeriveInput) -> TokenStream {
+    let input_span = input.span();
+    let struct_name = input.ident.clone();
+    // Both implementations are generated from the same parsed keys.
+    let parsed = match parse_keys(input, Derive::KeyBy, "KeyBy") {
+        Ok(parsed) => parsed,
+        Err(err) => return err.into_compile_error(),
+    };
+    let cmp_traits = gen_cmp_traits(
+        &parsed,
+        &struct_name,
+        input_span,
+        "KeyBy",
+        &[
+            CmpTrait::Eq,
+            CmpTrait::PartialEq,
+            CmpTrait::PartialOrd,
+            CmpTrait::Ord,
+        ],
+    );
+    let hash = gen_hash_impl(&parsed, &struct_name, input_span);
+    quote!(#cmp_traits #hash)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assert_rust_eq;
+
+    #[test]
+    fn test_struct() {
+        let input = syn::parse_quote! {
+            #[by(desc(len()), _fields)]
+            struct Sample {
+                #[by]
+                id: u32,
+                #[by(none_last, nan_first)]
+                value: Option<f32>,
+            }
+        };
+
+        let output = impl_key_by_derive(syn::parse2(input).unwrap());
+        assert_rust_eq!(
+            output.to_string(),
+            r#"impl ::core::cmp::Eq for Sample {}
+impl ::core::cmp::PartialEq<Self> for Sample {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self.id == other.id
+            && match (&self.value, &other.value) {
+                (::core::option::Option::Some(this), ::core::option::Option::Some(other)) => {
+                    match (this, other) {
+                        (this, other) => this == other || this.is_nan() && other.is_nan(),
+                    }
+                }
+                (::core::option::Option::None, ::core::option::Option::None) => true,
+                _ => false,
+            }
+    }
+}
+impl ::core::cmp::PartialOrd<Self> for Sample {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+        ::core::option::Option::Some(self.cmp(other))
+    }
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.cmp(other).is_lt()
+    }
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.cmp(other).is_le()
+    }
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.cmp(other).is_gt()
+    }
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.cmp(other).is_ge()
+    }
+}
+impl ::core::cmp::Ord for Sample {
+    #[inline]
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        self.len().cmp(&other.len()).reverse().then_with(|| {
+            self.id
+                .cmp(&other.id)
+                .then_with(|| match (&self.value, &other.value) {
+                    (::core::option::Option::Some(this), ::core::option::Option::Some(other)) => {
+                        match (this, other) {
+                            (this, other) => other.is_nan().cmp(&this.is_nan()).then_with(|| {
+                                this.partial_cmp(other)
+                                    .unwrap_or(::core::cmp::Ordering::Equal)
+                            }),
+                        }
+                    }
+                    (::core::option::Option::None, ::core::option::Option::None) => {
+                        ::core::cmp::Ordering::Equal
+                    }
+                    (::core::option::Option::None, ::core::option::Option::Some(_)) => {
+                        ::core::cmp::Ordering::Greater
+                    }
+                    (::core::option::Option::Some(_), ::core::option::Option::None) => {
+                        ::core::cmp::Ordering::Less
+                    }
+                })
+        })
+    }
+}
+impl ::core::hash::Hash for Sample {
+    fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        self.id.hash(state);
+        ::core::option::Option::as_ref(&self.value)
+            .map(|value| (!value.is_nan()).then(|| (*value + 0.0).to_bits()))
+            .hash(state);
+    }
+}
+"#
+        );
+    }
+}
@@ -15,7 +15,7 @@
 //! }
 //!
 //! #[derive(CmpBy, Debug)]
-//! #[cmp_by(channel(), pitch(), _fields)]
+//! #[cmp_by(common(channel), common(pitch), _fields)]
 //! enum Note {
 //! // ...
 //! #    NoteOn {
@@ -33,24 +33,6 @@
 //! #    }
 //! }
 //!
-//! impl Note {
-//!     fn channel(&self) -> Option<&u8> {
-//! #       match self {
-//! #           Note::CC => None,
-//! #           Note::NoteOn { channel, .. }
-//! #           | Note::NoteOff { channel, .. }
-//! #           | Note::Unsupported { channel, .. } => Some(channel),
-//! #       }
-//!     }
-//!
-//!     fn pitch(&self) -> Option<&u8> {
-//! #       match self {
-//! #           Note::NoteOn { pitch, .. } | Note::NoteOff { pitch, .. } => Some(pitch),
-//! #           _ => None,
-//! #       }
-//!     }
-//! }
-//!
 //! assert_eq!(
 //!     Midi {
 //!         global_time: 0,
@@ -138,13 +120,18 @@
 //! );
 //! ```
 //!
-//! Now I have a `Note` enum that will cmp by `global_time`, `channel`, `pitch`, and lastly by variant order ( `enum_sequence` ). Note that `None` is always less than `Some`.
+//! Now I have a `Note` enum that will cmp by `global_time`, `channel`, `pitch`, and lastly by variant order ( `enum_sequence` ).
+//! `common(channel)` projects each variant to `Some(&channel)`, or `None` for the variants without a `channel` field like `CC`.
+//! Note that `None` is less than `Some`, unless the items are wrapped in `none_last(...)`.
 //!
 //! Conversely, separate structs such as `NoteOn` may derive from `CmpBy` in order to ignore some fields ( ex: `velocity` may be a `f32`, so we can't directly derive `Ord` ).
+use crate::parsing::Derive;
 use syn::{parse_macro_input, DeriveInput};
 
 mod cmp_by;
+mod eq_by;
 mod hash_by;
+mod key_by;
 mod parsing;
 
 /// Fields that should be used for comparing are marked with the attribute `#[cmp_by]`.
@@ -164,6 +151,7 @@ mod parsing;
 /// assert_eq!(Something{a: 2, b: 0}.cmp(&Something{a: 1, b: 1}), Ordering::Greater); // a is compared
 /// assert_eq!(Something{a: 1, b: 0}.cmp(&Something{a: 1, b: 1}), Ordering::Equal); // b is ignored
 /// ```
+///
 /// You can use it the same way with tuple structs:
 ///
 /// ```rust
@@ -183,6 +171,33 @@ mod parsing;
 /// assert_eq!(Something(2, 0, 1.0).cmp(&Something(1, 0, 2.0)), Ordering::Greater); // Compares only specified fields
 /// ```
 ///
+/// The generated `PartialEq` tests the same keys with `==` instead of calling `cmp`, so it stops as soon as a key differs
+/// without finding out which value is greater. Run `cargo bench` for a comparison of both.
+///
+/// Fields of enum variants can be marked as well. Values of the same variant are compared by their marked fields,
+/// while values of different variants are ordered by variant declaration order.
+///
+/// ```rust
+/// # use std::cmp::Ordering;
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// enum Shape {
+///     Circle(#[cmp_by] u32, f32),
+///     Rectangle {
+///         #[cmp_by]
+///         width: u32,
+///         #[cmp_by]
+///         height: u32,
+///         angle: f32,
+///     },
+/// }
+///
+/// assert_eq!(Shape::Circle(1, 0.5).cmp(&Shape::Circle(1, 0.2)), Ordering::Equal);
+/// assert_eq!(Shape::Rectangle { width: 1, height: 2, angle: 0.0 }.cmp(&Shape::Rectangle { width: 1, height: 1, angle: 0.0 }), Ordering::Greater);
+/// assert_eq!(Shape::Circle(9, 0.0).cmp(&Shape::Rectangle { width: 1, height: 1, angle: 0.0 }), Ordering::Less);
+/// ```
+///
 ///
 /// Alternatively to, or in combination with field selectors, a struct-level or enum-level `#[cmp_by(method1(),method2(),attr1,nested.attr)]` can be declared.
 /// The top-level `cmp_by` attribute takes a list of attributes or method calls; items will be prepended with `self.`.
@@ -211,6 +226,10 @@ mod parsing;
 ///
 /// By default, this top-level declaration takes precedence, field comparisons will be considered if top-level comparisons are all `eq`.
 /// You can override this evaluation order by inserting the `_fields` reserved keyword for this derive macro: `#[cmp_by(method1(), _fields, method2())]`
+/// A key that compares the same value as an earlier one, like a field both listed and marked, is rejected since it is
+/// never reached: values get to it only when they are already equal on that value. Only keys following a projection
+/// of the value, like `(name, key = str::len), name`, or a float ordering with another equality, like
+/// `nan_last(x), total(x)`, are allowed.
 ///
 ///
 /// ```rust
@@ -235,12 +254,498 @@ mod parsing;
 /// assert_eq!(Something{a: 1, b: 0}.cmp(&Something{a: 2, b: 3}), Ordering::Less); // member comparison is equal (1 = 1) so fall back to method comparison
 /// ```
 ///
+/// Keys are compared in ascending order by default. Mark a field with `#[cmp_by(desc)]`, or wrap a top-level item in
+/// `desc(...)`, to compare it in descending order instead. `rev` is an alias of `desc`, and `asc` spells out the default.
+///
+/// ```rust
+/// # use std::cmp::Ordering;
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// #[cmp_by(desc(score()), _fields)]
+/// struct Player {
+///     #[cmp_by(asc)]
+///     name: &'static str,
+///     points: Vec<u32>,
+/// }
+///
+/// impl Player {
+///     fn score(&self) -> u32 {
+///         self.points.iter().sum()
+///     }
+/// }
+///
+/// let mut leaderboard = vec![
+///     Player { name: "b", points: vec![1, 2] },
+///     Player { name: "c", points: vec![4] },
+///     Player { name: "a", points: vec![3] },
+/// ];
+/// leaderboard.sort();
+/// assert_eq!(leaderboard.iter().map(|p| p.name).collect::<Vec<_>>(), ["c", "a", "b"]); // score descending, then name ascending
+/// ```
+///
+/// To compare a key with something else than `Ord::cmp`, give a function taking two references to the key and returning an
+/// `Ordering` with `#[cmp_by(with = path::to::function)]`. Top-level items take options after the item, either inside a
+/// direction like `desc(score(), with = path::to::function)` or in parentheses like `(name(), with = path::to::function)`.
+///
+/// ```rust
+/// # use std::cmp::Ordering;
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// fn cmp_ci(a: &str, b: &str) -> Ordering {
+///     a.to_lowercase().cmp(&b.to_lowercase())
+/// }
+///
+/// #[derive(CmpBy)]
+/// #[cmp_by((name(), with = cmp_ci))]
+/// struct Something {
+///     #[cmp_by(with = f32::total_cmp)]
+///     a: f32,
+///     name: String,
+/// }
+///
+/// impl Something {
+///     fn name(&self) -> &str {
+///         &self.name
+///     }
+/// }
+///
+/// assert_eq!(Something{a: 1.0, name: "a".into()}.cmp(&Something{a: 1.0, name: "A".into()}), Ordering::Equal);
+/// assert_eq!(Something{a: 1.0, name: "a".into()}.cmp(&Something{a: 2.0, name: "A".into()}), Ordering::Less);
+/// ```
+///
+/// A key can be projected before being compared with `key = ...`, which takes a closure or a path to a function receiving a
+/// reference to the key. A top-level `key = ...` item receives a reference to the whole value, which saves writing a
+/// method only to compare on it.
+///
+/// ```rust
+/// # use std::cmp::Ordering;
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// #[cmp_by(key = |s| s.name.len())]
+/// struct Something {
+///     name: String,
+///     #[cmp_by(key = str::len)]
+///     nickname: String,
+/// }
+///
+/// assert_eq!(Something{name: "ab".into(), nickname: "z".into()}.cmp(&Something{name: "c".into(), nickname: "yy".into()}), Ordering::Greater);
+/// assert_eq!(Something{name: "ab".into(), nickname: "z".into()}.cmp(&Something{name: "cd".into(), nickname: "yy".into()}), Ordering::Less);
+/// ```
+///
+/// Floating-point keys can be compared with the `total`, `nan_first` and `nan_last` options:
+/// - `total` compares with `total_cmp`, which orders `-0.0` before `0.0`, and NaNs by sign and payload.
+/// - `nan_first` and `nan_last` consider all NaNs equal and place them before or after every number, whatever the sort
+///   direction. Numbers are compared as usual, so `-0.0` equals `0.0`.
+///
+/// ```rust
+/// # use std::cmp::Ordering;
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// struct Measure {
+///     #[cmp_by(desc, nan_last)]
+///     value: f32,
+///     #[cmp_by(total)]
+///     error: f64,
+/// }
+///
+/// let mut measures = vec![
+///     Measure { value: f32::NAN, error: 0.0 },
+///     Measure { value: 1.0, error: 0.0 },
+///     Measure { value: 2.0, error: 0.0 },
+/// ];
+/// measures.sort();
+/// assert_eq!(measures[0].value, 2.0);
+/// assert_eq!(measures[1].value, 1.0);
+/// assert!(measures[2].value.is_nan());
+/// ```
+///
+/// `Option` keys can be given `none_first` or `none_last` to choose where `None` is placed, whatever the sort direction.
+/// Without them, `None` is less than `Some`.
+///
+/// ```rust
+/// # use std::cmp::Ordering;
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// #[cmp_by(desc(none_last(score())))]
+/// struct Row {
+///     score: Option<u32>,
+/// }
+///
+/// impl Row {
+///     fn score(&self) -> Option<u32> {
+///         self.score
+///     }
+/// }
+///
+/// let mut rows = vec![Row { score: None }, Row { score: Some(1) }, Row { score: Some(2) }];
+/// rows.sort();
+/// assert_eq!(rows.iter().map(|r| r.score).collect::<Vec<_>>(), [Some(2), Some(1), None]);
+/// ```
+///
+/// Values of different enum variants are ordered like the variants are declared.
+/// List every variant in `#[cmp_by(variant_order = [..])]`, or give each one a `#[cmp_by(rank = N)]`, to order them differently:
+///
+/// ```rust
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// #[cmp_by(variant_order = [Critical, Warning, Info])]
+/// enum Severity {
+///     Info,
+///     Warning,
+///     Critical,
+/// }
+///
+/// #[derive(CmpBy)]
+/// enum Status {
+///     #[cmp_by(rank = 2)]
+///     Done,
+///     #[cmp_by(rank = 0)]
+///     Todo,
+///     #[cmp_by(rank = 1)]
+///     InProgress,
+/// }
+///
+/// assert!(Severity::Critical < Severity::Info);
+/// assert!(Status::Todo < Status::InProgress);
+/// assert!(Status::InProgress < Status::Done);
+/// ```
+///
+/// On enums with explicit discriminants, `#[cmp_by(discriminant)]` orders the variants by their discriminant values instead,
+/// which have the type given by `#[repr(..)]`:
+///
+/// ```rust
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// #[cmp_by(discriminant)]
+/// #[repr(u8)]
+/// enum Status {
+///     ControlChange(u8) = 0xB0,
+///     NoteOff(u8) = 0x80,
+///     NoteOn(u8), // 0x81
+/// }
+///
+/// assert!(Status::NoteOff(0) < Status::NoteOn(0));
+/// assert!(Status::NoteOn(0) < Status::ControlChange(0));
+/// ```
+///
+/// Values of different variants are compared by variant after the top-level items.
+/// Insert the `_variant` reserved keyword to compare the variants elsewhere, like before the top-level items.
+/// The marked fields are only compared between values of the same variant.
+///
+/// ```rust
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// #[cmp_by(_variant, priority())]
+/// enum Job {
+///     Build(u8),
+///     Test(u8),
+/// }
+///
+/// impl Job {
+///     fn priority(&self) -> u8 {
+///         match self {
+///             Job::Build(p) | Job::Test(p) => *p,
+///         }
+///     }
+/// }
+///
+/// assert!(Job::Build(9) < Job::Test(0)); // the variant is compared first
+/// assert!(Job::Test(0) < Job::Test(1));
+/// ```
+///
+/// A variant's own attribute lists the keys its values are compared on, in order, before its marked fields.
+/// Keys are fields of the variant or paths into them like `header.seq`, and take the same options as the
+/// top-level items. Tuple variants name their fields by index, and `_fields` places the marked fields.
+///
+/// ```rust
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// enum Event {
+///     #[cmp_by(velocity, desc(pitch))]
+///     Note { pitch: u8, velocity: u8 },
+///     #[cmp_by(header.seq)]
+///     Sysex { header: Header, data: Vec<u8> },
+/// }
+///
+/// #[derive(PartialEq, Eq, PartialOrd, Ord)]
+/// struct Header {
+///     seq: u32,
+/// }
+///
+/// assert!(Event::Note { pitch: 9, velocity: 1 } < Event::Note { pitch: 0, velocity: 2 });
+/// assert!(Event::Note { pitch: 2, velocity: 1 } < Event::Note { pitch: 1, velocity: 1 });
+/// ```
+///
+/// `common(field)` compares a field shared by several variants, as an `Option<&T>` which is `None` for the variants
+/// without that field. Tuple variants name their fields by index: `common(0)`.
+/// With `common(field, required)`, every variant must have the field, which is then compared as a `&T`.
+///
+/// ```rust
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// #[cmp_by(common(deadline, required), common(assignee))]
+/// enum Task {
+///     Open { deadline: u32 },
+///     Assigned { deadline: u32, assignee: String },
+/// }
+///
+/// assert!(Task::Assigned { deadline: 1, assignee: "Zoe".into() } < Task::Open { deadline: 2 });
+/// assert!(Task::Open { deadline: 2 } < Task::Assigned { deadline: 2, assignee: "Ann".into() });
+/// ```
+///
+/// Unit structs and structs without fields, like `struct S {}`, need no marked fields: all their values are equal. Enums without variants have no values to compare.
+///
+/// The type parameters mentioned by the compared fields are bound by `Ord` in the generated impls, so
+/// `struct Wrapper<T, U> { #[cmp_by] key: T, payload: Vec<U> }` is `Ord` when `T` is, whatever `U`.
+/// Fields that aren't compared, `PhantomData` fields, and keys compared `with` a function or projected by a `key`
+/// add no bound.
+///
+/// When these bounds are wrong, the `bound` option of a field or item gives its where predicates instead,
+/// and `#[cmp_by(bound = "...")]` replaces all the inferred ones, an empty string giving none:
+///
+/// ```rust
+/// # use cmp_by_derive::CmpBy;
+/// # use std::fmt::Display;
+/// # use std::rc::Rc;
+/// #
+/// #[derive(CmpBy)]
+/// #[cmp_by((label(), bound = "T: Display"))]
+/// struct Shared<T> {
+///     #[cmp_by(bound = "T: Ord")]
+///     value: Rc<T>,
+/// }
+///
+/// impl<T: Display> Shared<T> {
+///     fn label(&self) -> String {
+///         self.value.to_string()
+///     }
+/// }
+///
+/// assert!(Shared { value: Rc::new(1) } < Shared { value: Rc::new(2) });
+/// ```
+///
+/// The fields of a `#[repr(packed)]` struct may be unaligned and cannot be referenced, so its keys are copied out of it
+/// before being compared. The marked fields of a packed struct must therefore be `Copy`, and so must the fields that
+/// items access or call methods on, like `inner` in `#[cmp_by(inner.get())]`:
+///
+/// ```rust
+/// # use cmp_by_derive::CmpBy;
+/// #
+/// #[derive(CmpBy)]
+/// #[repr(C, packed)]
+/// struct Header {
+///     #[cmp_by]
+///     version: u8,
+///     #[cmp_by]
+///     length: u32,
+/// }
+///
+/// assert!(Header { version: 1, length: 20 } < Header { version: 1, length: 40 });
+/// ```
+///
+/// `#[cmp_by(cmp_key)]` also generates a private `cmp_key(&self)` method on structs, or one with the visibility given
+/// by `#[cmp_by(cmp_key = "pub(crate)")]`, returning the keys as a tuple in the order they are compared. `Ord` is then
+/// implemented by comparing these tuples, so the method can stand in for the ordering, as a `BTreeMap` range bound for
+/// instance. Fields are borrowed, keys sorted `desc` are wrapped in [`Reverse`](core::cmp::Reverse), and method calls
+/// and projections are returned as `impl Ord` values. Every key is evaluated, even when the first ones already differ.
+/// Keys compared `with` a function, by a float ordering, with `none_last` ascending or with `none_first` descending
+/// have no `Ord` value to return and are rejected, and so are more than 12 keys, which tuples don't order. The method comes with `Ord`, so derives that
+/// leave it out reject the option.
+///
+/// ```rust
+/// # use cmp_by_derive::CmpBy;
+/// # use std::cmp::Reverse;
+/// #
+/// #[derive(CmpBy)]
+/// #[cmp_by(cmp_key, desc(priority))]
+/// struct Task {
+///     priority: u8,
+///     #[cmp_by]
+///     name: String,
+/// }
+///
+/// let task = Task { priority: 1, name: "build".into() };
+/// assert_eq!(task.cmp_key(), (Reverse(&1), &"build".to_string()));
+/// ```
+///
 #[proc_macro_derive(CmpBy, attributes(cmp_by))]
 pub fn cmp_by_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     cmp_by::impl_cmp_by_derive(ast).into()
 }
 
+/// Derives `PartialEq` from the keys selected with `#[cmp_by]`, exactly like [`CmpBy`](derive@CmpBy) does.
+///
+/// Use it along with the other comparison derives when some of the traits are implemented by hand.
+///
+/// ```rust
+/// # use cmp_by_derive::PartialEqBy;
+/// #
+/// #[derive(PartialEqBy)]
+/// struct Something {
+///     #[cmp_by]
+///     a: u16,
+///     b: f32,
+/// }
+///
+/// assert!(Something { a: 1, b: 0.0 } == Something { a: 1, b: 1.0 });
+/// ```
+#[proc_macro_derive(PartialEqBy, attributes(cmp_by))]
+pub fn partial_eq_by_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    cmp_by::impl_cmp_traits(
+        ast,
+        Derive::CmpBy,
+        "PartialEqBy",
+        &[cmp_by::CmpTrait::PartialEq],
+    )
+    .into()
+}
+
+/// Derives `Eq` for types whose `PartialEq` is a total equivalence, such as one derived by [`PartialEqBy`](derive@PartialEqBy).
+///
+/// Unlike `#[derive(Eq)]`, it does not require every field to be `Eq`, only the keys selected with `#[cmp_by]`.
+/// It is the standalone `Eq` of [`CmpBy`](derive@CmpBy), while [`EqBy`](derive@EqBy) derives `PartialEq` as well from `#[eq_by]` keys.
+///
+/// ```rust
+/// # use cmp_by_derive::{EqOnlyBy, PartialEqBy};
+/// #
+/// #[derive(PartialEqBy, EqOnlyBy)]
+/// struct Something {
+///     #[cmp_by]
+///     a: u16,
+///     b: f32,
+/// }
+///
+/// assert!(Something { a: 1, b: 0.0 } == Something { a: 1, b: 1.0 });
+/// ```
+#[proc_macro_derive(EqOnlyBy, attributes(cmp_by))]
+pub fn eq_only_by_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    cmp_by::impl_cmp_traits(ast, Derive::CmpBy, "EqOnlyBy", &[cmp_by::CmpTrait::Eq]).into()
+}
+
+/// Derives `Eq` and `PartialEq` by testing the fields marked with `#[eq_by]` for equality with `==`.
+///
+/// Like with [`PartialEqBy`](derive@PartialEqBy), the keys only need to be `PartialEq`, not `Ord`,
+/// and the comparison stops at the first key that differs.
+/// Along with [`HashBy`](derive@HashBy) on the same keys, this makes types usable as `HashMap` keys:
+///
+/// ```rust
+/// # use std::collections::{HashMap, HashSet};
+/// # use cmp_by_derive::{EqBy, HashBy};
+/// #
+/// #[derive(EqBy, HashBy)]
+/// struct Sensor {
+///     #[eq_by]
+///     #[hash_by]
+///     id: u32,
+///     readings: HashMap<String, f32>,
+/// }
+///
+/// let mut sensors = HashSet::new();
+/// sensors.insert(Sensor { id: 1, readings: HashMap::new() });
+/// assert!(sensors.contains(&Sensor { id: 1, readings: HashMap::from([("t".into(), 1.0)]) }));
+/// ```
+///
+/// Container-level items work like with [`CmpBy`](derive@CmpBy), `_fields` included,
+/// and so do the `with = path::to::function` and `key = ...` options, the function returning whether its two arguments are equal.
+/// Float keys marked with `total` are equal when `total_cmp` finds them equal, like with [`CmpBy`](derive@CmpBy), so a NaN is
+/// equal to itself but `-0.0` is not equal to `0.0`:
+///
+/// ```rust
+/// # use cmp_by_derive::{EqBy, HashBy};
+/// #
+/// #[derive(EqBy, HashBy)]
+/// #[eq_by((name(), with = str::eq_ignore_ascii_case))]
+/// struct Point {
+///     name: String,
+///     #[eq_by(total)]
+///     #[hash_by(total)]
+///     x: f32,
+/// }
+///
+/// impl Point {
+///     fn name(&self) -> &str {
+///         &self.name
+///     }
+/// }
+///
+/// assert!(Point { name: "A".into(), x: f32::NAN } == Point { name: "a".into(), x: f32::NAN });
+/// assert!(Point { name: "A".into(), x: -0.0 } != Point { name: "a".into(), x: 0.0 });
+/// assert!(Point { name: "A".into(), x: 1.0 } != Point { name: "B".into(), x: 1.0 });
+/// ```
+///
+/// Options that only affect ordering, like `desc` or `none_last`, are rejected.
+#[proc_macro_derive(EqBy, attributes(eq_by))]
+pub fn eq_by_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    eq_by::impl_eq_by_derive(ast).into()
+}
+
+/// Derives `PartialOrd` from the keys selected with `#[cmp_by]`, exactly like [`CmpBy`](derive@CmpBy) does.
+///
+/// ```rust
+/// # use std::cmp::Ordering;
+/// # use cmp_by_derive::PartialOrdBy;
+/// #
+/// #[derive(PartialOrdBy)]
+/// struct Something {
+///     #[cmp_by(desc)]
+///     a: u16,
+///     b: f32,
+/// }
+///
+/// impl PartialEq for Something {
+///     fn eq(&self, other: &Self) -> bool {
+///         self.a == other.a
+///     }
+/// }
+///
+/// assert!(Something { a: 2, b: 0.0 } < Something { a: 1, b: 1.0 });
+/// ```
+#[proc_macro_derive(PartialOrdBy, attributes(cmp_by))]
+pub fn partial_ord_by_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    cmp_by::impl_cmp_traits(
+        ast,
+        Derive::CmpBy,
+        "PartialOrdBy",
+        &[cmp_by::CmpTrait::PartialOrd],
+    )
+    .into()
+}
+
+/// Derives `Ord` from the keys selected with `#[cmp_by]`, exactly like [`CmpBy`](derive@CmpBy) does.
+///
+/// ```rust
+/// # use std::cmp::Ordering;
+/// # use cmp_by_derive::{EqOnlyBy, OrdBy, PartialEqBy, PartialOrdBy};
+/// #
+/// #[derive(PartialEqBy, EqOnlyBy, PartialOrdBy, OrdBy)]
+/// struct Something {
+///     #[cmp_by]
+///     a: u16,
+///     b: f32,
+/// }
+///
+/// assert_eq!(Something { a: 1, b: 0.0 }.cmp(&Something { a: 2, b: 1.0 }), Ordering::Less);
+/// ```
+#[proc_macro_derive(OrdBy, attributes(cmp_by))]
+pub fn ord_by_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    cmp_by::impl_cmp_traits(ast, Derive::CmpBy, "OrdBy", &[cmp_by::CmpTrait::Ord]).into()
+}
+
 /// Fields that should be used for hashing are marked with the attribute `#[hash_by]`.
 /// Other fields will be ignored.
 ///
@@ -291,10 +796,111 @@ pub fn cmp_by_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 /// }
 /// ```
 ///
-/// Because hashing is not order dependent, there is no point for the `_fields` reserved keyword for this derive, so it isn't included.
+/// Because hashing is not order dependent, there is no point for the `_fields` reserved keyword for this derive, so it is rejected.
+///
+/// To hash a key with something else than `Hash::hash`, give a function taking a reference to the key and the hasher with
+/// `#[hash_by(with = path::to::function)]`, or `(item, with = path::to::function)` for top-level items.
+///
+/// ```rust
+/// # use cmp_by_derive::HashBy;
+/// # use std::hash::{Hash, Hasher};
+/// #
+/// fn hash_bits<H: Hasher>(a: &f32, state: &mut H) {
+///     a.to_bits().hash(state)
+/// }
+///
+/// #[derive(HashBy)]
+/// struct Something {
+///     #[hash_by(with = hash_bits)]
+///     a: f32,
+/// }
+/// ```
+///
+/// Keys can also be projected before being hashed with `key = ...`, just like with `CmpBy`.
+///
+/// ```rust
+/// # use cmp_by_derive::HashBy;
+/// #
+/// #[derive(HashBy)]
+/// #[hash_by(key = |s| s.name.to_lowercase())]
+/// struct Something {
+///     name: String,
+/// }
+/// ```
+///
+/// Floating-point keys can be hashed with `#[hash_by(total)]`, which hashes `-0.0` like `0.0` and all NaNs alike, so that
+/// values equal under any float ordering of `CmpBy` or `EqBy` hash the same.
+///
+/// ```rust
+/// # use cmp_by_derive::{CmpBy, HashBy};
+/// #
+/// #[derive(CmpBy, HashBy)]
+/// struct Measure {
+///     #[cmp_by(nan_last)]
+///     #[hash_by(total)]
+///     value: f32,
+/// }
+/// ```
+///
+/// On enums, every variant hashes its discriminant before its marked fields, so that hashing agrees with the variant
+/// ordering of `CmpBy`. Variants without marked fields hash only their discriminant.
+/// Like with `CmpBy`, the `_variant` keyword hashes the discriminant among the top-level items instead: `#[hash_by(kind(), _variant)]`,
+/// and `#[hash_by(discriminant)]` hashes the discriminant values given by the enum's `#[repr(..)]` instead of the variant indices.
+/// `#[hash_by(common(field))]` hashes a field shared by several variants just like `CmpBy` compares it.
+/// Unit structs and enums without variants hash nothing.
+/// Like with `CmpBy`, the type parameters mentioned by the hashed fields are bound by `Hash` in the generated impl,
+/// unless `bound = "..."` gives the where predicates.
+/// The keys of a `#[repr(packed)]` struct are copied out of it to be hashed, and must be `Copy`.
+///
+/// Equal values must hash the same, so when the type derives its equality from `#[eq_by]` or `#[cmp_by]` keys,
+/// every hashed field must be one of them, projected by the same `key`, and hashed `with` a function when it is compared
+/// with one. Hashed methods and other values computed from the fields are trusted to only depend on the tested ones.
+/// `#[hash_by(allow_inconsistent_hash)]` turns this check off, like when the type hashes a cache of its value on purpose.
+/// The `#[cmp_by]` keys are assumed to give the equality, which a derive cannot verify: when `PartialEq` comes from
+/// elsewhere and they only serve `PartialOrdBy` and `OrdBy`, the check must be turned off too.
 ///
 #[proc_macro_derive(HashBy, attributes(hash_by))]
 pub fn hash_by_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     hash_by::impl_hash_by_derive(ast).into()
 }
+
+/// Derives `Eq`, `PartialEq`, `Ord`, `PartialOrd` and `Hash` from the keys selected with `#[by]`,
+/// so that the keys are written once and values that are equal always hash the same.
+///
+/// `#[by]` takes the keys and options of `#[cmp_by]`:
+///
+/// ```rust
+/// # use cmp_by_derive::KeyBy;
+/// # use std::collections::HashSet;
+/// #
+/// #[derive(KeyBy)]
+/// #[by(desc(priority()), _fields)]
+/// struct Task {
+///     #[by(key = |name| name.to_lowercase())]
+///     name: String,
+///     #[by(total)]
+///     weight: f32,
+///     urgent: bool,
+/// }
+///
+/// impl Task {
+///     fn priority(&self) -> u8 {
+///         if self.urgent { 1 } else { 0 }
+///     }
+/// }
+///
+/// let task = |name: &str, urgent| Task { name: name.into(), weight: 1.0, urgent };
+/// assert!(task("b", true) < task("a", false));
+///
+/// let tasks: HashSet<_> = [task("Write", false), task("write", false)].into_iter().collect();
+/// assert_eq!(tasks.len(), 1);
+/// ```
+///
+/// Floats are hashed like `#[hash_by(total)]` does, whatever their ordering.
+/// `with` isn't supported, since comparing and hashing a key would need different functions: project it with `key` instead.
+#[proc_macro_derive(KeyBy, attributes(by))]
+pub fn key_by_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    key_by::impl_key_by_derive(ast).into()
+}
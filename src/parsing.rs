@@ -1,47 +1,1007 @@
 #![allow(clippy::manual_try_fold)]
 
-use proc_macro2::{Span, TokenStream};
-use quote::{quote, ToTokens};
+use std::cmp::Reverse;
+
+use proc_macro2::{Literal, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
-    parse2, parse_quote, punctuated::Punctuated, spanned::Spanned, ConstParam, Data, DataEnum,
-    DataStruct, DeriveInput, Error, Expr, Fields, FieldsNamed, FieldsUnnamed, GenericArgument,
-    GenericParam, Generics, Index, LifetimeParam, Token, TypeParam,
+    parse_quote, parse_quote_spanned, punctuated::Punctuated, spanned::Spanned, Attribute, Data,
+    DataEnum, DataStruct, DeriveInput, Error, Expr, ExprArray, ExprAssign, ExprCall, ExprField,
+    ExprLit, ExprMethodCall, ExprPath, ExprTuple, Fields, FieldsNamed, FieldsUnnamed, Generics,
+    Ident, Index, Lit, LitInt, Member, Meta, Path, Token, Type, TypePath, Variant, Visibility,
+    WherePredicate,
 };
 
+/// The derive macro being expanded, which determines the attribute that is read and the options it accepts.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
+pub(crate) enum Derive {
+    CmpBy,
+    EqBy,
+    HashBy,
+    /// Derives the comparison traits and `Hash` from the same `#[by]` keys.
+    KeyBy,
+}
+
+impl Derive {
+    pub fn attr(self) -> &'static str {
+        match self {
+            Derive::CmpBy => "cmp_by",
+            Derive::EqBy => "eq_by",
+            Derive::HashBy => "hash_by",
+            Derive::KeyBy => "by",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Derive::CmpBy => "CmpBy",
+            Derive::EqBy => "EqBy",
+            Derive::HashBy => "HashBy",
+            Derive::KeyBy => "KeyBy",
+        }
+    }
+
+    /// Whether the derive implements an ordering, which accepts the options that only affect ordering.
+    pub fn orders(self) -> bool {
+        matches!(self, Derive::CmpBy | Derive::KeyBy)
+    }
+}
+
+/// Something to compare or hash on, along with the options it was declared with.
+pub struct Key<T> {
+    pub value: T,
+    pub options: KeyOptions,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Ascending,
+    Descending,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    First,
+    Last,
+}
+
+/// How floating-point keys are compared.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FloatOrder {
+    /// With `total_cmp`, given by `total`.
+    Total,
+    /// NaNs are all equal and sorted before or after every number, given by `nan_first` or `nan_last`.
+    Nan(Position),
+}
+
+/// Options given in a field attribute, like `#[cmp_by(desc)]`, or around a container-level item, like `#[cmp_by(desc(score()))]`.
+#[derive(Default)]
+pub struct KeyOptions {
+    pub direction: Option<Direction>,
+    pub float: Option<FloatOrder>,
+    /// Where `None` is placed for `Option` keys, given by `none_first` or `none_last`.
+    pub none: Option<Position>,
+    /// Function called instead of `Ord::cmp`, `PartialEq::eq` or `Hash::hash`, given by `with = path::to::function`.
+    pub with: Option<Path>,
+    /// Projection applied to the key before comparing or hashing it, given by `key = |value| ...` or `key = path::to::function`.
+    pub key: Option<Expr>,
+    /// Where predicates replacing the bounds inferred from the key's type, given by `bound = "T: Ord"`.
+    pub bound: Option<Vec<WherePredicate>>,
+}
+
+/// Options that can also wrap a container-level item.
+const MODIFIERS: &[&str] = &[
+    "asc",
+    "desc",
+    "rev",
+    "total",
+    "nan_first",
+    "nan_last",
+    "none_first",
+    "none_last",
+];
+
+/// Options written as `name = value`, with an example of each.
+const VALUE_OPTIONS: &[(&str, &str)] = &[
+    ("with", "with = path::to::function"),
+    ("key", "key = |value| ..."),
+    ("bound", r#"bound = "T: Ord""#),
+];
+
+impl KeyOptions {
+    pub fn is_descending(&self) -> bool {
+        self.direction == Some(Direction::Descending)
+    }
+
+    /// Whether `other` gives the same options, the expressions being compared by their tokens.
+    fn same_as(&self, other: &Self) -> bool {
+        fn tokens(value: Option<&impl ToTokens>) -> Option<String> {
+            value.map(|value| value.to_token_stream().to_string())
+        }
+        let predicates = |options: &Self| {
+            tokens(
+                options
+                    .bound
+                    .as_ref()
+                    .map(|bound| quote!(#(#bound),*))
+                    .as_ref(),
+            )
+        };
+        self.direction == other.direction
+            && self.float == other.float
+            && self.none == other.none
+            && tokens(self.with.as_ref()) == tokens(other.with.as_ref())
+            && tokens(self.key.as_ref()) == tokens(other.key.as_ref())
+            && predicates(self) == predicates(other)
+    }
+
+    /// Sets the flag option named `ident`, returning `false` if `ident` is not a flag option.
+    fn set_flag(&mut self, ident: &Ident, derive: Derive) -> syn::Result<bool> {
+        match ident.to_string().as_str() {
+            "asc" => {
+                ordering_only(ident, derive)?;
+                set_once(
+                    &mut self.direction,
+                    Direction::Ascending,
+                    ident,
+                    "the sort direction",
+                )?;
+            }
+            "desc" | "rev" => {
+                ordering_only(ident, derive)?;
+                set_once(
+                    &mut self.direction,
+                    Direction::Descending,
+                    ident,
+                    "the sort direction",
+                )?;
+            }
+            "total" => set_once(
+                &mut self.float,
+                FloatOrder::Total,
+                ident,
+                "the float ordering",
+            )?,
+            "nan_first" => {
+                ordering_only(ident, derive)?;
+                let order = FloatOrder::Nan(Position::First);
+                set_once(&mut self.float, order, ident, "the float ordering")?;
+            }
+            "nan_last" => {
+                ordering_only(ident, derive)?;
+                let order = FloatOrder::Nan(Position::Last);
+                set_once(&mut self.float, order, ident, "the float ordering")?;
+            }
+            "none_first" => {
+                ordering_only(ident, derive)?;
+                set_once(
+                    &mut self.none,
+                    Position::First,
+                    ident,
+                    "the position of `None`",
+                )?;
+            }
+            "none_last" => {
+                ordering_only(ident, derive)?;
+                set_once(
+                    &mut self.none,
+                    Position::Last,
+                    ident,
+                    "the position of `None`",
+                )?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Sets the option written as `name = value`, returning `false` if `name` is not such an option.
+    fn set_value(&mut self, name: &Ident, value: Expr, derive: Derive) -> syn::Result<bool> {
+        match name.to_string().as_str() {
+            // Comparing and hashing would each need their own function.
+            "with" if derive == Derive::KeyBy => {
+                return Err(Error::new(
+                    name.span(),
+                    "`with` is not supported by KeyBy, which compares and hashes the same keys: \
+                     project the key with `key = ...` instead",
+                ));
+            }
+            "with" => {
+                let Expr::Path(ExprPath {
+                    qself: None, path, ..
+                }) = value
+                else {
+                    return Err(Error::new(
+                        value.span(),
+                        "expected a path to a function: `with = path::to::function`",
+                    ));
+                };
+                set_once(&mut self.with, path, name, "`with`")?;
+            }
+            "key" => set_once(&mut self.key, value, name, "`key`")?,
+            "bound" => set_once(&mut self.bound, parse_bound(value)?, name, "`bound`")?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Parses an option of a field attribute or of a parenthesized container-level item.
+    fn parse_option(&mut self, option: Expr, derive: Derive) -> syn::Result<()> {
+        let Some(name) = option_name(&option).cloned() else {
+            return Err(Error::new(
+                option.span(),
+                format!(
+                    "expected a `{}` option, like `desc` or `with = path::to::function`",
+                    derive.attr()
+                ),
+            ));
+        };
+        let has_value = matches!(option, Expr::Assign(_));
+        let known = match option {
+            Expr::Assign(ExprAssign { right, .. }) => self.set_value(&name, *right, derive)?,
+            _ => self.set_flag(&name, derive)?,
+        };
+        if known {
+            return Ok(());
+        }
+        let message = if let Some((_, example)) = VALUE_OPTIONS.iter().find(|(n, _)| name == n) {
+            format!("`{name}` expects a value: `{example}`")
+        } else if has_value && MODIFIERS.iter().any(|m| name == m) {
+            format!("`{name}` is a flag and takes no value")
+        } else {
+            // Only the options of the same form supported by the derive are suggested.
+            let candidates: Vec<&str> = if has_value {
+                VALUE_OPTIONS.iter().map(|(name, _)| *name).collect()
+            } else {
+                MODIFIERS
+                    .iter()
+                    .copied()
+                    .filter(|m| derive.orders() || *m == "total")
+                    .collect()
+            };
+            let unknown = format!("unknown `{}` option `{name}`", derive.attr());
+            match closest(&name.to_string(), candidates) {
+                Some(candidate) => format!("{unknown}, did you mean `{candidate}`?"),
+                None => unknown,
+            }
+        };
+        Err(Error::new(name.span(), message))
+    }
+}
+
+/// The candidate closest to `name`, if it is close enough to be a typo of it.
+///
+/// Among equally close candidates, the one sharing the longest prefix with `name` wins, so `dsc` suggests `desc`
+/// rather than `asc`.
+fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = name.len().max(3) / 3;
+    let common_prefix = |candidate: &str| {
+        name.chars()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| a == b)
+            .count()
+    };
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, candidate)| (*distance, Reverse(common_prefix(candidate))))
+        .map(|(_, candidate)| candidate)
+}
+
+/// Number of insertions, deletions, substitutions and transpositions of adjacent characters turning `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    // `distances[i][j]` is the distance between the first `i` characters of `a` and the first `j` of `b`.
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+fn set_once<T>(option: &mut Option<T>, value: T, ident: &Ident, what: &str) -> syn::Result<()> {
+    if option.is_some() {
+        return Err(Error::new(
+            ident.span(),
+            format!("{what} is specified more than once"),
+        ));
+    }
+    *option = Some(value);
+    Ok(())
+}
+
+fn ordering_only(ident: &Ident, derive: Derive) -> syn::Result<()> {
+    if derive.orders() {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ident.span(),
+            format!(
+                "`{ident}` only affects ordering, it is not supported by {}",
+                derive.name()
+            ),
+        ))
+    }
+}
+
+/// Parses the where predicates of `bound = "T: Ord, U: Display"`, an empty string giving no bound at all.
+fn parse_bound(value: Expr) -> syn::Result<Vec<WherePredicate>> {
+    let Expr::Lit(ExprLit {
+        lit: Lit::Str(predicates),
+        ..
+    }) = value
+    else {
+        return Err(Error::new(
+            value.span(),
+            r#"expected a string of where predicates: `bound = "T: Ord"`"#,
+        ));
+    };
+    // Errors in the predicates span the whole string.
+    let predicates = predicates
+        .parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)
+        .map_err(|err| Error::new(predicates.span(), err))?;
+    Ok(predicates.into_iter().collect())
+}
+
+/// Parses the visibility given as a string by `cmp_key = "pub(crate)"`.
+fn parse_visibility(value: Expr) -> syn::Result<Visibility> {
+    let Expr::Lit(ExprLit {
+        lit: Lit::Str(vis), ..
+    }) = value
+    else {
+        return Err(Error::new(
+            value.span(),
+            r#"expected a string of the method's visibility: `cmp_key = "pub(crate)"`"#,
+        ));
+    };
+    vis.parse().map_err(|err| Error::new(vis.span(), err))
+}
+
+/// Name of an option written either as `name` or as `name = value`.
+fn option_name(option: &Expr) -> Option<&Ident> {
+    match option {
+        Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) => path.get_ident(),
+        Expr::Assign(ExprAssign { left, .. }) => option_name(left),
+        _ => None,
+    }
+}
+
 pub enum ParsedFields {
-    Struct(Vec<Expr>),
-    Enum(Vec<(TokenStream, Vec<Expr>)>),
+    Struct(Vec<Key<Member>>),
+    Enum(Vec<ParsedVariant>),
+}
+
+impl ParsedFields {
+    /// Whether this is an enum without variants, which has no values to compare or hash.
+    pub fn is_empty_enum(&self) -> bool {
+        matches!(self, ParsedFields::Enum(variants) if variants.is_empty())
+    }
+}
+
+pub struct ParsedVariant {
+    pub ident: Ident,
+    pub style: VariantStyle,
+    pub fields: Vec<Key<FieldPath>>,
+    /// Values of different variants compare like their ranks, which follow the declaration order
+    /// unless given by `variant_order = [..]` or `rank = N`.
+    pub rank: Expr,
+}
+
+/// Key of an enum variant: one of its fields, or a path into one like `header.seq`.
+pub struct FieldPath {
+    /// Field of the variant, bound by [`ParsedVariant::binding_pattern`].
+    pub member: Member,
+    /// Fields accessed in turn from `member`.
+    pub path: Vec<Member>,
+}
+
+impl FieldPath {
+    /// Parses a path like `field`, `0` or `header.seq` from a variant attribute.
+    pub fn parse(expr: &Expr) -> syn::Result<Self> {
+        match expr {
+            Expr::Path(ExprPath {
+                qself: None, path, ..
+            }) if path.get_ident().is_some() => Ok(FieldPath {
+                member: Member::Named(
+                    path.get_ident()
+                        .expect("Checked by the match guard.")
+                        .clone(),
+                ),
+                path: Vec::new(),
+            }),
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(index),
+                ..
+            }) => Ok(FieldPath {
+                member: Member::Unnamed(Index {
+                    index: index.base10_parse()?,
+                    span: index.span(),
+                }),
+                path: Vec::new(),
+            }),
+            Expr::Field(ExprField { base, member, .. }) => {
+                let mut field_path = Self::parse(base)?;
+                field_path.path.push(member.clone());
+                Ok(field_path)
+            }
+            _ => Err(Error::new(
+                expr.span(),
+                "expected a field of the variant or a path into one: `field` or `field.inner`",
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum VariantStyle {
+    Named,
+    Unnamed,
+    Unit,
+}
+
+impl ParsedVariant {
+    /// Pattern matching this variant without binding any of its fields.
+    pub fn pattern(&self) -> TokenStream {
+        let ident = &self.ident;
+        match self.style {
+            VariantStyle::Named => quote!(Self::#ident { .. }),
+            VariantStyle::Unnamed => quote!(Self::#ident(..)),
+            VariantStyle::Unit => quote!(Self::#ident),
+        }
+    }
+
+    /// Pattern matching this variant and binding its field `member` to `binding`.
+    pub fn member_pattern(&self, member: &Member, binding: &Ident) -> TokenStream {
+        let ident = &self.ident;
+        match member {
+            Member::Named(name) => quote!(Self::#ident { #name: #binding, .. }),
+            Member::Unnamed(Index { index, .. }) => {
+                let skipped = (0..*index).map(|_| quote!(_));
+                quote!(Self::#ident(#(#skipped,)* #binding, ..))
+            }
+        }
+    }
+
+    /// Fields the keys of this variant go through, each one once.
+    fn bound_members(&self) -> Vec<&Member> {
+        let mut members = Vec::new();
+        for Key { value, .. } in &self.fields {
+            if !members.contains(&&value.member) {
+                members.push(&value.member);
+            }
+        }
+        members
+    }
+
+    /// Pattern matching this variant and binding every marked field to `<prefix>_<field>`.
+    pub fn binding_pattern(&self, prefix: &str) -> TokenStream {
+        let ident = &self.ident;
+        match self.style {
+            VariantStyle::Named => {
+                let bindings = self.bound_members().into_iter().map(|member| {
+                    let binding = binding(prefix, member);
+                    quote!(#member: #binding)
+                });
+                quote!(Self::#ident { #(#bindings,)* .. })
+            }
+            VariantStyle::Unnamed => {
+                let members = self.bound_members();
+                let len = members
+                    .iter()
+                    .filter_map(|member| match member {
+                        Member::Unnamed(Index { index, .. }) => Some(*index as usize + 1),
+                        Member::Named(_) => None,
+                    })
+                    .max()
+                    .unwrap_or_default();
+                let mut positions = vec![quote!(_); len];
+                for member in members {
+                    if let Member::Unnamed(Index { index, .. }) = member {
+                        positions[*index as usize] = binding(prefix, member).into_token_stream();
+                    }
+                }
+                quote!(Self::#ident(#(#positions,)* ..))
+            }
+            VariantStyle::Unit => quote!(Self::#ident),
+        }
+    }
+}
+
+/// How a key is reached in the generated code.
+#[derive(Clone)]
+pub struct Operand {
+    /// Expression of the key itself, used as the receiver of method calls.
+    pub receiver: Expr,
+    /// Reference to the key, used as a function argument.
+    pub reference: Expr,
+}
+
+impl Operand {
+    /// Key accessed through `base`, like `self.field` or `other.method()`.
+    pub fn access(base: TokenStream, expr: &impl ToTokens) -> Self {
+        let span = expr.span();
+        Operand {
+            receiver: parse_quote_spanned!(span => #base.#expr),
+            reference: parse_quote_spanned!(span => &#base.#expr),
+        }
+    }
+
+    /// Field of a struct accessed through `base`, copied out of the struct when it is `#[repr(packed)]`.
+    pub fn field(base: TokenStream, member: &Member, packed: bool) -> Self {
+        let operand = Self::access(base, member);
+        if packed {
+            operand.copied()
+        } else {
+            operand
+        }
+    }
+
+    /// Container-level item accessed through `base`, which is either `self` or `other`.
+    ///
+    /// Paths into the fields of a `#[repr(packed)]` struct are copied out of it, like its marked fields, and so are
+    /// the fields that methods are called on, like `inner` in `inner.get()`.
+    pub fn item(base: TokenStream, item: &Item, packed: bool) -> Self {
+        match item {
+            Item::Expr(expr @ (Expr::Path(_) | Expr::Field(_) | Expr::Lit(_))) if packed => {
+                Self::access(base, expr).copied()
+            }
+            Item::Expr(expr) if packed => {
+                let span = expr.span();
+                let receiver = copy_fields(&base, expr);
+                Operand {
+                    reference: parse_quote_spanned!(span => &#receiver),
+                    receiver: parse_quote_spanned!(span => #receiver),
+                }
+            }
+            Item::Expr(expr) => Self::access(base, expr),
+            Item::Value(span) => Operand {
+                receiver: parse_quote_spanned!(*span => #base),
+                reference: parse_quote_spanned!(*span => #base),
+            },
+            // A `required` field is projected to a reference, which is dereferenced so that the
+            // value is compared like any other field.
+            Item::Common(Common {
+                span,
+                arms,
+                required: true,
+                ..
+            }) => Operand {
+                receiver: parse_quote_spanned!(*span => (*match #base { #(#arms,)* })),
+                reference: parse_quote_spanned!(*span => &*match #base { #(#arms,)* }),
+            },
+            Item::Common(Common { span, arms, .. }) => Operand {
+                receiver: parse_quote_spanned!(*span => (match #base { #(#arms,)* })),
+                reference: parse_quote_spanned!(*span => &match #base { #(#arms,)* }),
+            },
+        }
+    }
+
+    /// Enum field bound by [`ParsedVariant::binding_pattern`], which is already a reference.
+    pub fn binding(prefix: &str, FieldPath { member, path }: &FieldPath) -> Self {
+        let binding = binding(prefix, member);
+        if path.is_empty() {
+            return Self::reference(binding);
+        }
+        Operand {
+            receiver: parse_quote!(#binding #(.#path)*),
+            reference: parse_quote!(&#binding #(.#path)*),
+        }
+    }
+
+    /// Key reached through a variable holding a reference to it.
+    pub fn reference(ident: Ident) -> Self {
+        Operand {
+            receiver: parse_quote!(#ident),
+            reference: parse_quote!(#ident),
+        }
+    }
+
+    /// Copies the key into a temporary, since the fields of a `#[repr(packed)]` struct may be unaligned and cannot be
+    /// referenced.
+    fn copied(self) -> Self {
+        let receiver = self.receiver;
+        Operand {
+            reference: parse_quote_spanned!(receiver.span() => &{ #receiver }),
+            receiver: parse_quote_spanned!(receiver.span() => ({ #receiver })),
+        }
+    }
+
+    /// Applies the `key` projection of `options`, if any.
+    ///
+    /// Closures are passed to the function generated by [`project_key_fn`], which gives them their argument type.
+    /// Other projections are called directly, so that deref coercion applies to their argument, like with `key = str::len`.
+    pub fn project(self, options: &KeyOptions) -> Self {
+        let Some(key) = &options.key else {
+            return self;
+        };
+        let reference = self.reference;
+        let projected: Expr = if let Expr::Closure(_) = key {
+            parse_quote_spanned!(key.span() => __project_key(#reference, #key))
+        } else {
+            parse_quote_spanned!(key.span() => (#key)(#reference))
+        };
+        Operand {
+            reference: parse_quote_spanned!(key.span() => &#projected),
+            receiver: projected,
+        }
+    }
+}
+
+/// Accesses `expr` through `base`, copying the fields it calls methods on or accesses the fields of out of a
+/// `#[repr(packed)]` struct, like `{ self.inner }.get()` for `inner.get()`.
+fn copy_fields(base: &TokenStream, expr: &Expr) -> TokenStream {
+    match expr {
+        Expr::Path(_) | Expr::Field(_) | Expr::Lit(_) if FieldPath::parse(expr).is_ok() => {
+            quote_spanned!(expr.span() => ({ #base.#expr }))
+        }
+        Expr::Field(ExprField {
+            base: inner,
+            dot_token,
+            member,
+            ..
+        }) => {
+            let inner = copy_fields(base, inner);
+            quote!(#inner #dot_token #member)
+        }
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            dot_token,
+            method,
+            turbofish,
+            args,
+            ..
+        }) => {
+            let receiver = copy_fields(base, receiver);
+            quote!(#receiver #dot_token #method #turbofish(#args))
+        }
+        // Methods of the struct itself, like `name()`, take it by reference.
+        _ => quote_spanned!(expr.span() => #base.#expr),
+    }
+}
+
+/// Field of the struct that [`copy_fields`] copies out of a packed struct to access `expr`, if any.
+fn copied_field(mut expr: &Expr) -> Option<Member> {
+    loop {
+        if let Ok(field) = FieldPath::parse(expr) {
+            return Some(field.member);
+        }
+        expr = match expr {
+            Expr::Field(ExprField { base, .. }) => base,
+            Expr::MethodCall(ExprMethodCall { receiver, .. }) => receiver,
+            _ => return None,
+        };
+    }
+}
+
+/// Helper function through which closure projections are called, see [`Operand::project`].
+pub fn project_key_fn() -> TokenStream {
+    quote! {
+        #[inline(always)]
+        fn __project_key<'a, T: ?::core::marker::Sized, K>(value: &'a T, key: impl ::core::ops::FnOnce(&'a T) -> K) -> K {
+            key(value)
+        }
+    }
+}
+
+/// Identifier a marked enum field is bound to in the patterns built by [`ParsedVariant::binding_pattern`].
+fn binding(prefix: &str, member: &Member) -> Ident {
+    match member {
+        Member::Named(ident) => format_ident!("{}_{}", prefix, ident, span = ident.span()),
+        Member::Unnamed(Index { index, span }) => {
+            format_ident!("{}_{}", prefix, index, span = *span)
+        }
+    }
+}
+
+/// Item of the container-level attribute.
+pub enum Item {
+    /// Field or method of the value, like `inner.field` or `method()`.
+    Expr(Expr),
+    /// The value itself, for items only made of options like `key = |value| ...`.
+    Value(Span),
+    /// Field shared by several variants of an enum, given by `common(field)`.
+    Common(Common),
+}
+
+/// Field shared by several variants of an enum, which is `None` for the variants that lack it,
+/// unless it is `required` in every variant.
+pub struct Common {
+    pub member: Member,
+    pub required: bool,
+    pub span: Span,
+    /// Arms of the match projecting the value to the field, filled by [`resolve_common`] once the variants are known.
+    pub arms: Vec<TokenStream>,
+}
+
+impl Item {
+    pub fn span(&self) -> Span {
+        match self {
+            Item::Expr(expr) => expr.span(),
+            Item::Value(span) | Item::Common(Common { span, .. }) => *span,
+        }
+    }
+
+    /// Whether this is the `_fields` keyword, which places the comparison of the marked fields among the container-level items.
+    pub fn is_fields_keyword(&self) -> bool {
+        matches!(self, Item::Expr(expr) if expr.to_token_stream().to_string() == "_fields")
+    }
+
+    /// Whether this is the `_variant` keyword, which places the comparison of enum variants among the container-level items.
+    pub fn is_variant_keyword(&self) -> bool {
+        matches!(self, Item::Expr(expr) if expr.to_token_stream().to_string() == "_variant")
+    }
+}
+
+/// Method returning the keys of a struct, generated by the `cmp_key` option.
+pub struct CmpKey {
+    pub name: Ident,
+    /// Private unless given by `cmp_key = "pub(crate)"`, since the method could expose private key types.
+    pub vis: Visibility,
 }
 
 pub struct ParsedInput {
-    pub expressions: Vec<Expr>,
+    pub expressions: Vec<Key<Item>>,
     pub fields: ParsedFields,
     pub generics: Generics,
-    pub generic_arguments: Vec<GenericArgument>,
+    /// Integer type of the enum's discriminant, when `discriminant` makes the variants' ranks their discriminant values.
+    pub discriminant_type: Option<Ident>,
+    /// Type parameters mentioned by the compared fields, which must implement the derived trait.
+    pub bounded_params: Vec<Type>,
+    /// Where predicates given by the `bound` options, which replace the inferred ones.
+    pub bounds: Vec<WherePredicate>,
+    /// Types of the fields of a `#[repr(packed)]` struct that are copied out of it to be compared, the marked ones and
+    /// those the items start from.
+    pub packed: Option<Vec<Type>>,
+    /// Whether `allow_inconsistent_hash` lets HashBy hash keys that the derived equality doesn't test.
+    pub allow_inconsistent_hash: bool,
+    /// The `cmp_key` option, which generates a method returning the keys of a struct.
+    pub cmp_key: Option<CmpKey>,
+    /// Members of a struct along with their types, empty for enums.
+    pub members: Vec<(Member, Type)>,
+}
+
+/// Key of the values, named by its tokens to be matched with the keys of another derive.
+pub struct KeyName<'a> {
+    /// Variant whose values have the key, `None` for a key of every value.
+    pub variant: Option<&'a Ident>,
+    pub name: String,
+    pub options: &'a KeyOptions,
+    pub span: Span,
+    /// Whether the key is a field or a path into one, rather than a value computed from the fields like a method call.
+    pub is_field: bool,
+}
+
+impl ParsedInput {
+    /// Whether any key is projected by a closure, which needs [`project_key_fn`] in scope.
+    pub fn has_key_closures(&self) -> bool {
+        let is_closure = |options: &KeyOptions| matches!(options.key, Some(Expr::Closure(_)));
+        let fields = match &self.fields {
+            ParsedFields::Struct(fields) => {
+                fields.iter().map(|key| &key.options).collect::<Vec<_>>()
+            }
+            ParsedFields::Enum(variants) => variants
+                .iter()
+                .flat_map(|v| &v.fields)
+                .map(|key| &key.options)
+                .collect(),
+        };
+        self.expressions.iter().any(|key| is_closure(&key.options))
+            || fields.into_iter().any(is_closure)
+    }
+
+    /// Keys of the container-level items, the marked fields and the variant keys, without the `_fields` and `_variant`
+    /// keywords. A field is named the same whether it is marked or listed.
+    pub fn key_names(&self) -> Vec<KeyName<'_>> {
+        let mut names = Vec::new();
+        for Key { value, options } in &self.expressions {
+            let (name, is_field) = match value {
+                _ if value.is_fields_keyword() || value.is_variant_keyword() => continue,
+                Item::Expr(expr) => (
+                    expr.to_token_stream().to_string(),
+                    FieldPath::parse(expr).is_ok(),
+                ),
+                Item::Value(_) => ("self".to_string(), false),
+                Item::Common(Common { member, .. }) => {
+                    (format!("common({})", member.to_token_stream()), true)
+                }
+            };
+            let span = value.span();
+            names.push(KeyName {
+                variant: None,
+                name,
+                options,
+                span,
+                is_field,
+            });
+        }
+        match &self.fields {
+            ParsedFields::Struct(fields) => {
+                names.extend(fields.iter().map(|Key { value, options }| KeyName {
+                    variant: None,
+                    name: value.to_token_stream().to_string(),
+                    options,
+                    span: value.span(),
+                    is_field: true,
+                }))
+            }
+            ParsedFields::Enum(variants) => {
+                for variant in variants {
+                    names.extend(variant.fields.iter().map(
+                        |Key {
+                             value: FieldPath { member, path },
+                             options,
+                         }| KeyName {
+                            variant: Some(&variant.ident),
+                            name: quote!(#member #(.#path)*).to_string(),
+                            options,
+                            span: member.span(),
+                            is_field: true,
+                        },
+                    ))
+                }
+            }
+        }
+        names
+    }
+
+    /// Items the generated methods need in scope: [`project_key_fn`] when closures project the keys, and the check
+    /// that the fields copied out of a packed struct are `Copy`.
+    pub fn prelude(&self) -> TokenStream {
+        let project_key = self.has_key_closures().then(project_key_fn);
+        let copy_check = self.packed.as_deref().map(packed_copy_check);
+        quote!(#project_key #copy_check)
+    }
 }
 
-pub(crate) fn parse_input(input: DeriveInput, attr: &str) -> Result<ParsedInput, ParsingError> {
+/// Asserts that the fields of a packed struct are `Copy`, with an error on each field that is not.
+///
+/// Copying a field that is not `Copy` out of the struct would fail with a less helpful error about moving out of it,
+/// which the compiler doesn't report once this check has failed.
+fn packed_copy_check(types: &[Type]) -> TokenStream {
+    let checks = types
+        .iter()
+        .map(|ty| quote_spanned!(ty.span() => __packed_field::<#ty>();));
+    quote! {
+        #[diagnostic::on_unimplemented(
+            message = "the fields of a `#[repr(packed)]` struct must be `Copy` to be used as keys, `{Self}` is not",
+            label = "cannot be copied out of the packed struct",
+            note = "fields of packed structs may be unaligned and cannot be referenced"
+        )]
+        trait __PackedField {}
+        impl<T: ::core::marker::Copy> __PackedField for T {}
+        fn __packed_field<T: __PackedField>() {}
+        #(#checks)*
+    }
+}
+
+pub(crate) fn parse_input(input: DeriveInput, derive: Derive) -> Result<ParsedInput, ParsingError> {
     // println!("Entered parse_input()");
-    let expressions = input
+    let attr = derive.attr();
+    let is_enum = matches!(input.data, Data::Enum(_));
+    let mut variant_order = None;
+    let mut discriminant = None;
+    let mut container_bound = None;
+    let mut allow_inconsistent_hash = None;
+    let mut cmp_key = None;
+    let mut expressions = input
         .attrs
         .iter()
-        .filter(|i| i.path().get_ident().map_or(false, |i| {
-            i == attr
-        }))
+        .filter(|i| i.path().get_ident().is_some_and(|i| i == attr))
         .map(|attr| {
-            attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?.into_iter().map(|elem| {
-                match elem {
-                    Expr::Call(_) | Expr::Field(_) | Expr::Path(_) | Expr::MethodCall(_) => {
-                        // TODO: test paths with lengths other than 1
-                        Ok(elem)
+            attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?
+                .into_iter()
+                .filter_map(|elem| match elem {
+                    Expr::Assign(ExprAssign { left, right, .. })
+                        if option_name(&left).is_some_and(|name| name == "variant_order") =>
+                    {
+                        let name = option_name(&left).expect("Checked by the match guard.");
+                        ordering_only(name, derive)
+                            .and_then(|()| {
+                                set_once(
+                                    &mut variant_order,
+                                    (name.clone(), *right),
+                                    name,
+                                    "`variant_order`",
+                                )
+                            })
+                            .map_err(ParsingError::Error)
+                            .err()
+                            .map(Err)
                     }
-                    _ => {
-                        Err(ParsingError::Error(Error::new(elem.span(), format!("Invalid form: `{}`.\nAllowed forms: `field`, `method()`, `inner.field`, `inner.method()", elem.to_token_stream()))))
+                    // Alone, `bound` replaces the inferred bounds, rather than those of an item.
+                    Expr::Assign(ExprAssign { left, right, .. })
+                        if option_name(&left).is_some_and(|name| name == "bound") =>
+                    {
+                        let name = option_name(&left).expect("Checked by the match guard.");
+                        parse_bound(*right)
+                            .and_then(|bound| set_once(&mut container_bound, bound, name, "`bound`"))
+                            .map_err(ParsingError::Error)
+                            .err()
+                            .map(Err)
                     }
-                }
-            }).fold(Ok(vec![]), fold_token_errors)
-        }).fold(Ok(vec![]), |acc, res| {
-        match (acc, res) {
+                    // Enums have no field to compare on, so `discriminant` is the option on them.
+                    Expr::Path(ExprPath { ref path, .. }) if is_enum && path.is_ident("discriminant") => {
+                        let name = path.get_ident().expect("Checked by the match guard.");
+                        if derive == Derive::EqBy {
+                            let error = Error::new(
+                                name.span(),
+                                "`discriminant` only affects ordering and hashing, it is not supported by EqBy",
+                            );
+                            return Some(Err(ParsingError::Error(error)));
+                        }
+                        set_once(&mut discriminant, name.clone(), name, "`discriminant`")
+                            .map_err(ParsingError::Error)
+                            .err()
+                            .map(Err)
+                    }
+                    Expr::Path(ExprPath { ref path, .. }) if path.is_ident("allow_inconsistent_hash") => {
+                        let name = path.get_ident().expect("Checked by the match guard.");
+                        if derive != Derive::HashBy {
+                            let error = Error::new(
+                                name.span(),
+                                format!("`allow_inconsistent_hash` only applies to HashBy, not {}", derive.name()),
+                            );
+                            return Some(Err(ParsingError::Error(error)));
+                        }
+                        set_once(&mut allow_inconsistent_hash, (), name, "`allow_inconsistent_hash`")
+                            .map_err(ParsingError::Error)
+                            .err()
+                            .map(Err)
+                    }
+                    // Rather than a key, `cmp_key` is the method returning them.
+                    Expr::Path(ExprPath { ref path, .. }) if path.is_ident("cmp_key") => {
+                        let name = path.get_ident().expect("Checked by the match guard.");
+                        let method = CmpKey {
+                            name: name.clone(),
+                            vis: Visibility::Inherited,
+                        };
+                        ordering_only(name, derive)
+                            .and_then(|()| set_once(&mut cmp_key, method, name, "`cmp_key`"))
+                            .map_err(ParsingError::Error)
+                            .err()
+                            .map(Err)
+                    }
+                    Expr::Assign(ExprAssign { left, right, .. })
+                        if option_name(&left).is_some_and(|name| name == "cmp_key") =>
+                    {
+                        let name = option_name(&left).expect("Checked by the match guard.");
+                        ordering_only(name, derive)
+                            .and_then(|()| parse_visibility(*right))
+                            .and_then(|vis| {
+                                let method = CmpKey {
+                                    name: name.clone(),
+                                    vis,
+                                };
+                                set_once(&mut cmp_key, method, name, "`cmp_key`")
+                            })
+                            .map_err(ParsingError::Error)
+                            .err()
+                            .map(Err)
+                    }
+                    elem => Some(parse_container_item(elem, derive).map_err(ParsingError::Error)),
+                })
+                .fold(Ok(vec![]), fold_token_errors)
+        })
+        .fold(Ok(vec![]), |acc, res| match (acc, res) {
             (Ok(mut acc), Ok(res)) => {
                 acc.extend(res);
                 Ok(acc)
@@ -51,74 +1011,926 @@ pub(crate) fn parse_input(input: DeriveInput, attr: &str) -> Result<ParsedInput,
                 Err(acc)
             }
             (Ok(_), Err(err)) | (Err(err), Ok(_)) => Err(err),
-        }
-    })?;
+        })?;
     // println!("Successfully parsed expressions");
+    let repr = parse_repr(&input.attrs)?;
+    let mut discriminant_type = None;
+    let mut field_types = Vec::new();
+    let mut packed = None;
+    let mut members = Vec::new();
 
     let fields = match input.data {
-        Data::Struct(DataStruct {
-            fields: fields @ (Fields::Unnamed(..) | Fields::Named(..)),
-            ..
-        }) => {
+        Data::Struct(DataStruct { fields, .. }) => {
             // println!("Parsing struct fields");
-            ParsedFields::Struct(parse_fields(&fields, attr)?)
+            if let Some((name, _)) = variant_order {
+                return Err(ParsingError::Error(Error::new(
+                    name.span(),
+                    "`variant_order` only applies to enums",
+                )));
+            }
+            if let Some(Key {
+                value: Item::Expr(keyword),
+                ..
+            }) = expressions
+                .iter()
+                .find(|key| key.value.is_variant_keyword())
+            {
+                return Err(ParsingError::Error(Error::new(
+                    keyword.span(),
+                    "`_variant` only applies to enums",
+                )));
+            }
+            if let Some(Key {
+                value: Item::Common(common),
+                ..
+            }) = expressions
+                .iter()
+                .find(|key| matches!(key.value, Item::Common(_)))
+            {
+                return Err(ParsingError::Error(Error::new(
+                    common.span,
+                    "`common` only applies to enums, access the field directly instead",
+                )));
+            }
+            let types = typed_members(&fields);
+            let fields = match parse_fields(&fields, derive) {
+                Ok(f) => f,
+                // Container-level items are enough to compare on.
+                Err(ParsingError::NoField(_)) if !expressions.is_empty() => Vec::new(),
+                Err(e) => return Err(e),
+            };
+            reject_duplicate_keys(&expressions, &fields, derive)?;
+            if repr.packed {
+                // Items copy the field they start from, which is `Copy` only if all of its own fields are.
+                let mut copied: Vec<Member> = fields.iter().map(|key| key.value.clone()).collect();
+                for key in &expressions {
+                    if let Item::Expr(expr) = &key.value {
+                        if let Some(member) = copied_field(expr).filter(|m| !copied.contains(m)) {
+                            copied.push(member);
+                        }
+                    }
+                }
+                packed = Some(
+                    copied
+                        .iter()
+                        .filter_map(|member| types.iter().find(|(field, _)| field == member))
+                        .map(|(_, ty)| ty.clone())
+                        .collect(),
+                );
+            }
+            members.clone_from(&types);
+            field_types.push(types);
+            ParsedFields::Struct(fields)
         }
         Data::Enum(DataEnum { variants, .. }) => {
             // println!("Parsing enum fields");
-            ParsedFields::Enum(
-                variants
-                    .into_iter()
-                    .map(|variant| -> Result<_, ParsingError> {
-                        let result = match parse_fields(&variant.fields, attr) {
-                            Ok(f) => f,
-                            Err(ParsingError::NoField(_)) => Vec::new(),
-                            Err(e) => return Err(e),
-                        };
-                        let field_pat = match variant.fields {
-                            Fields::Named(_) => {
-                                quote!({ .. })
-                            }
-                            Fields::Unnamed(_) => {
-                                quote!((..))
-                            }
-                            Fields::Unit => {
-                                quote!()
-                            }
-                        };
+            reject_duplicate_keys(&expressions, &[], derive)?;
+            if let Some(CmpKey { name, .. }) = cmp_key {
+                return Err(ParsingError::Error(Error::new(
+                    name.span(),
+                    "`cmp_key` only applies to structs",
+                )));
+            }
+            let mut ranks = Vec::new();
+            let mut discriminants = Vec::new();
+            let mut members = Vec::new();
+            let mut variants = variants
+                .into_iter()
+                .enumerate()
+                .map(|(index, variant)| -> Result<_, ParsingError> {
+                    let mut attrs = variant
+                        .attrs
+                        .iter()
+                        .filter(|i| i.path().get_ident().is_some_and(|i| i == attr));
+                    let variant_attr = attrs.next();
+                    if attrs.next().is_some() {
+                        return Err(ParsingError::Error(Error::new(
+                            variant.span(),
+                            format!(r#"expected at most one `{attr}` attribute"#),
+                        )));
+                    }
+                    let VariantOptions { rank, keys: listed } = variant_attr
+                        .map(|variant_attr| parse_variant_options(variant_attr, derive))
+                        .transpose()?
+                        .unwrap_or_default();
+                    ranks.push(rank);
+                    let marked = match parse_fields(&variant.fields, derive) {
+                        Ok(f) => f,
+                        Err(ParsingError::NoField(_)) => Vec::new(),
+                        Err(e) => return Err(e),
+                    };
+                    let result = variant_keys(&variant, listed, marked, derive)?;
+                    let style = match variant.fields {
+                        Fields::Named(_) => VariantStyle::Named,
+                        Fields::Unnamed(_) => VariantStyle::Unnamed,
+                        Fields::Unit => VariantStyle::Unit,
+                    };
 
-                        let ident = variant.ident;
-                        Ok((quote!(Self::#ident #field_pat), result))
+                    discriminants.push(variant.discriminant.map(|(_, value)| value));
+                    members.push(variant.fields.members().collect::<Vec<_>>());
+                    field_types.push(typed_members(&variant.fields));
+                    let rank = Literal::usize_unsuffixed(index);
+                    Ok(ParsedVariant {
+                        ident: variant.ident,
+                        style,
+                        fields: result,
+                        rank: parse_quote!(#rank),
                     })
-                    .fold(Ok(vec![]), fold_token_errors)?,
-            )
+                })
+                .fold(Ok(vec![]), fold_token_errors)?;
+            if let Some(name) = discriminant {
+                discriminant_type = Some(repr.integer);
+                rank_by_discriminant(&mut variants, &name, discriminants, variant_order, ranks)?;
+            } else {
+                rank_variants(&mut variants, variant_order, ranks, &input.ident)?;
+            }
+            resolve_common(
+                &mut expressions,
+                &variants.iter().zip(members).collect::<Vec<_>>(),
+            )?;
+            ParsedFields::Enum(variants)
         }
         _ => {
             return Err(ParsingError::Error(Error::new(
                 input.span(),
-                r#"expected an enum or a non-unit struct"#,
+                r#"expected an enum or a struct"#,
             )));
         }
     };
     // println!("Successfully parsed fields");
-    let generic_arguments = input
-        .generics
-        .params
-        .iter()
-        .flat_map(|p| match p {
-            GenericParam::Type(TypeParam { ident, .. })
-            | GenericParam::Const(ConstParam { ident, .. }) => Some(parse_quote!(#ident)),
-            GenericParam::Lifetime(LifetimeParam { lifetime, .. }) => Some(parse_quote!(#lifetime)),
-        })
-        .collect::<Vec<_>>();
     let generics = input.generics;
+    let bounds = given_bounds(&expressions, &fields, container_bound.as_deref());
+    let bounded_params = if container_bound.is_some() {
+        Vec::new()
+    } else {
+        bounded_params(&expressions, &fields, &field_types, &generics)
+    };
     Ok(ParsedInput {
         expressions,
         fields,
         generics,
-        generic_arguments,
+        discriminant_type,
+        bounded_params,
+        bounds,
+        packed,
+        allow_inconsistent_hash: allow_inconsistent_hash.is_some(),
+        cmp_key,
+        members,
     })
 }
 
+/// Options of a variant attribute.
+#[derive(Default)]
+struct VariantOptions {
+    /// `rank = N`, with the option's name for error spans.
+    rank: Option<(Ident, LitInt)>,
+    /// Keys to compare the variant's values on, before its marked fields.
+    keys: Vec<Key<Item>>,
+}
+
+/// Parses a variant attribute, which gives the variant's `rank = N` and the keys to compare its values on.
+fn parse_variant_options(attr: &Attribute, derive: Derive) -> syn::Result<VariantOptions> {
+    let mut rank = None;
+    let mut keys = Vec::new();
+    if !matches!(attr.meta, Meta::Path(_)) {
+        for option in attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)? {
+            match option {
+                Expr::Assign(ExprAssign { left, right, .. })
+                    if option_name(&left).is_some_and(|name| name == "rank") =>
+                {
+                    let name = option_name(&left).expect("Checked by the match guard.");
+                    ordering_only(name, derive)?;
+                    let Expr::Lit(ExprLit {
+                        lit: Lit::Int(value),
+                        ..
+                    }) = *right
+                    else {
+                        return Err(Error::new(right.span(), "expected an integer: `rank = 1`"));
+                    };
+                    value.base10_parse::<u64>()?;
+                    set_once(&mut rank, (name.clone(), value), name, "`rank`")?;
+                }
+                option => keys.push(parse_container_item(option, derive)?),
+            }
+        }
+    }
+    Ok(VariantOptions { rank, keys })
+}
+
+/// Orders the keys of a variant: the ones listed in its attribute, followed by its marked fields unless `_fields`
+/// places them among the listed keys.
+fn variant_keys(
+    variant: &Variant,
+    listed: Vec<Key<Item>>,
+    marked: Vec<Key<Member>>,
+    derive: Derive,
+) -> syn::Result<Vec<Key<FieldPath>>> {
+    reject_duplicate_keys(&listed, &marked, derive)?;
+    let mut marked = Some(marked.into_iter().map(|Key { value, options }| Key {
+        value: FieldPath {
+            member: value,
+            path: Vec::new(),
+        },
+        options,
+    }));
+    let mut keys = Vec::new();
+    for Key { value, options } in listed {
+        if value.is_fields_keyword() {
+            keys.extend(marked.take().into_iter().flatten());
+            continue;
+        }
+        let value =
+            match value {
+                Item::Expr(expr) if expr.to_token_stream().to_string() == "_variant" => {
+                    return Err(Error::new(
+                        expr.span(),
+                        "`_variant` only applies to the enum's attribute",
+                    ))
+                }
+                Item::Expr(expr) => FieldPath::parse(&expr)?,
+                Item::Value(span) | Item::Common(Common { span, .. }) => return Err(Error::new(
+                    span,
+                    "expected a field of the variant or a path into one: `field` or `field.inner`",
+                )),
+            };
+        if !variant
+            .fields
+            .members()
+            .any(|member| member == value.member)
+        {
+            return Err(Error::new(
+                value.member.span(),
+                format!(
+                    "no field `{}` in variant `{}`",
+                    value.member.to_token_stream(),
+                    variant.ident
+                ),
+            ));
+        }
+        keys.push(Key { value, options });
+    }
+    // Unless `_fields` has already placed them.
+    keys.extend(marked.into_iter().flatten());
+    Ok(keys)
+}
+
+/// Rejects the keys that are never reached, since an earlier key compares the same value: keys listed more than once,
+/// the listed fields that are also marked, and any key following the plain comparison of its value.
+///
+/// A key is only reached after a projection of the same value, like `(name, key = str::len), name`, which tells apart
+/// values the projection doesn't.
+fn reject_duplicate_keys(
+    listed: &[Key<Item>],
+    marked: &[Key<Member>],
+    derive: Derive,
+) -> syn::Result<()> {
+    // Values equal on a key are equal on any later key comparing the same value, unless the earlier key only compares
+    // a projection of the value, or tests floats for equality another way: unlike `nan_first` and `nan_last`, `total`
+    // tells apart `-0.0` from `0.0`, and NaNs with different payloads.
+    fn shadows(earlier: &KeyOptions, later: &KeyOptions) -> bool {
+        let projection = |options: &KeyOptions| {
+            (
+                options
+                    .with
+                    .as_ref()
+                    .map(|with| with.to_token_stream().to_string()),
+                options
+                    .key
+                    .as_ref()
+                    .map(|key| key.to_token_stream().to_string()),
+            )
+        };
+        let total = |options: &KeyOptions| options.float.map(|float| float == FloatOrder::Total);
+        total(earlier) == total(later)
+            && ((earlier.with.is_none() && earlier.key.is_none())
+                || projection(earlier) == projection(later))
+    }
+    let marked_keys = marked
+        .iter()
+        .map(|key| (key.value.to_token_stream().to_string(), &key.options, None));
+    // Keys in the order they are compared, with the span of the listed ones.
+    let mut keys: Vec<(String, &KeyOptions, Option<Span>)> = Vec::new();
+    for Key { value, options } in listed {
+        let key = match value {
+            _ if value.is_fields_keyword() => {
+                keys.extend(marked_keys.clone());
+                continue;
+            }
+            Item::Expr(expr) => expr.to_token_stream().to_string(),
+            Item::Common(Common { member, .. }) => format!("common({})", member.to_token_stream()),
+            // Projections of the value itself are not compared.
+            Item::Value(_) => continue,
+        };
+        keys.push((key, options, Some(value.span())));
+    }
+    // Marked fields are compared after the listed keys, unless `_fields` placed them.
+    if !listed.iter().any(|key| key.value.is_fields_keyword()) {
+        keys.extend(marked_keys);
+    }
+
+    let mut compared: Vec<&(String, &KeyOptions, Option<Span>)> = Vec::new();
+    let mut errors = Vec::new();
+    for later in &keys {
+        let (key, options, span) = later;
+        let Some((_, earlier_options, earlier_span)) = compared
+            .iter()
+            .find(|(seen, seen_options, _)| seen == key && shadows(seen_options, options))
+        else {
+            compared.push(later);
+            continue;
+        };
+        let message = match (span, earlier_span) {
+            (None, _) | (_, None) => format!(
+                "`{key}` is both listed and marked with `#[{}]`, keep one of them",
+                derive.attr()
+            ),
+            _ if options.same_as(earlier_options) => format!("`{key}` is listed more than once"),
+            _ if derive == Derive::HashBy => {
+                format!("`{key}` is already hashed by an earlier key, which makes hashing it again redundant")
+            }
+            _ => format!(
+                "`{key}` is already compared by an earlier key, so this key is never reached"
+            ),
+        };
+        let span = span
+            .or(*earlier_span)
+            .expect("Marked fields are never compared twice.");
+        errors.push(Error::new(span, message));
+    }
+    errors
+        .into_iter()
+        .reduce(|mut error, err| {
+            error.combine(err);
+            error
+        })
+        .map_or(Ok(()), Err)
+}
+
+/// Gives the variants the ranks listed by `variant_order = [..]` or given by their `rank = N` options, if any.
+///
+/// Every variant must be ranked exactly once, so that values of different variants are never equal.
+fn rank_variants(
+    variants: &mut [ParsedVariant],
+    variant_order: Option<(Ident, Expr)>,
+    ranks: Vec<Option<(Ident, LitInt)>>,
+    enum_ident: &Ident,
+) -> syn::Result<()> {
+    let mut errors = Vec::new();
+    if let Some((name, order)) = variant_order {
+        if let Some((rank_name, _)) = ranks.iter().flatten().next() {
+            errors.push(Error::new(
+                rank_name.span(),
+                "`rank` cannot be combined with `variant_order`",
+            ));
+        }
+        let Expr::Array(ExprArray { elems, .. }) = &order else {
+            return Err(Error::new(
+                order.span(),
+                "expected a list of variants: `variant_order = [A, B]`",
+            ));
+        };
+        let mut listed = vec![false; variants.len()];
+        for (rank, elem) in elems.iter().enumerate() {
+            let Some(ident) = option_name(elem).filter(|_| matches!(elem, Expr::Path(_))) else {
+                errors.push(Error::new(elem.span(), "expected a variant name"));
+                continue;
+            };
+            let Some(index) = variants.iter().position(|v| &v.ident == ident) else {
+                errors.push(Error::new(
+                    ident.span(),
+                    format!("no variant `{ident}` in enum `{enum_ident}`"),
+                ));
+                continue;
+            };
+            if listed[index] {
+                errors.push(Error::new(
+                    ident.span(),
+                    format!("variant `{ident}` is listed more than once"),
+                ));
+                continue;
+            }
+            listed[index] = true;
+            let rank = Literal::usize_unsuffixed(rank);
+            variants[index].rank = parse_quote!(#rank);
+        }
+        for (variant, _) in variants.iter().zip(listed).filter(|(_, listed)| !listed) {
+            errors.push(Error::new(
+                name.span(),
+                format!(
+                    "variant `{}` is missing from `variant_order`",
+                    variant.ident
+                ),
+            ));
+        }
+    } else if ranks.iter().any(Option::is_some) {
+        let mut given: Vec<(u64, &Ident)> = Vec::new();
+        for (variant, rank) in variants.iter_mut().zip(&ranks) {
+            let Some((_, value)) = rank else {
+                errors.push(Error::new(
+                    variant.ident.span(),
+                    format!(
+                        "variant `{}` has no `rank`, which every variant needs once one has it",
+                        variant.ident
+                    ),
+                ));
+                continue;
+            };
+            let number = value.base10_parse::<u64>()?;
+            if let Some((_, other)) = given.iter().find(|(n, _)| *n == number) {
+                errors.push(Error::new(
+                    value.span(),
+                    format!("rank {number} is already given to variant `{other}`"),
+                ));
+                continue;
+            }
+            given.push((number, &variant.ident));
+            // Unsuffixed, ranks above `i32::MAX` would overflow the `i32` they default to.
+            let rank = Literal::u64_suffixed(number);
+            variant.rank = parse_quote!(#rank);
+        }
+    }
+    errors
+        .into_iter()
+        .reduce(|mut acc, err| {
+            acc.combine(err);
+            acc
+        })
+        .map_or(Ok(()), Err)
+}
+
+/// Gives the variants their discriminant values as ranks, as asked by the `discriminant` option named `name`.
+///
+/// Variants without an explicit discriminant have the value of the previous variant plus one, or zero for the first one.
+fn rank_by_discriminant(
+    variants: &mut [ParsedVariant],
+    name: &Ident,
+    discriminants: Vec<Option<Expr>>,
+    variant_order: Option<(Ident, Expr)>,
+    ranks: Vec<Option<(Ident, LitInt)>>,
+) -> syn::Result<()> {
+    let conflicting = variant_order
+        .map(|(order_name, _)| order_name)
+        .into_iter()
+        .chain(ranks.into_iter().flatten().map(|(rank_name, _)| rank_name));
+    let mut errors = conflicting.map(|conflicting| {
+        Error::new(
+            conflicting.span(),
+            format!("`{conflicting}` cannot be combined with `{name}`"),
+        )
+    });
+    if let Some(mut error) = errors.next() {
+        errors.for_each(|err| error.combine(err));
+        return Err(error);
+    }
+
+    let mut previous: Option<Expr> = None;
+    let mut offset = 0usize;
+    for (variant, discriminant) in variants.iter_mut().zip(discriminants) {
+        if let Some(value) = discriminant {
+            previous = Some(value);
+            offset = 0;
+        }
+        let offset_literal = Literal::usize_unsuffixed(offset);
+        variant.rank = match &previous {
+            Some(value) if offset == 0 => value.clone(),
+            Some(value) => parse_quote!((#value) + #offset_literal),
+            None => parse_quote!(#offset_literal),
+        };
+        offset += 1;
+    }
+    Ok(())
+}
+
+/// Layout given by the `#[repr(..)]` attributes.
+struct Repr {
+    /// Integer type of the enum's discriminants, which is `isize` by default.
+    integer: Ident,
+    /// Whether `packed` or `packed(N)` lays out the struct, whose fields may then be unaligned.
+    packed: bool,
+}
+
+/// Parses the `#[repr(..)]` attributes, skipping the options that are not needed, like `C` or `align(..)`.
+fn parse_repr(attrs: &[Attribute]) -> syn::Result<Repr> {
+    const INTEGERS: &[&str] = &[
+        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+    ];
+    let mut integer = None;
+    let mut packed = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            packed |= meta.path.is_ident("packed");
+            if let Some(ident) = meta
+                .path
+                .get_ident()
+                .filter(|ident| INTEGERS.iter().any(|integer| ident == integer))
+            {
+                integer = Some(ident.clone());
+            } else if meta.input.peek(syn::token::Paren) {
+                // Skips the arguments of `align(..)` and `packed(..)`.
+                meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(Repr {
+        integer: integer.unwrap_or_else(|| format_ident!("isize")),
+        packed,
+    })
+}
+
+/// Parses an item of the container-level attribute, unwrapping the options around it,
+/// like in `desc(score())`, `desc(score(), with = path::to::function)` or `(name, with = path::to::function)`.
+fn parse_container_item(mut elem: Expr, derive: Derive) -> syn::Result<Key<Item>> {
+    let mut options = KeyOptions::default();
+    let mut wrapped = false;
+    loop {
+        elem = match elem {
+            Expr::Call(call) if modifier(&call.func).is_some() => {
+                let ident = modifier(&call.func).expect("Checked by the match guard.");
+                options.set_flag(ident, derive)?;
+                let mut args = call.args.into_iter();
+                let Some(item) = args.next() else {
+                    return Err(Error::new(
+                        call.paren_token.span.join(),
+                        format!("expected an item: `{ident}(item)`"),
+                    ));
+                };
+                for option in args {
+                    options.parse_option(option, derive)?;
+                }
+                item
+            }
+            Expr::Tuple(ExprTuple { elems, .. }) if elems.len() > 1 => {
+                let mut elems = elems.into_iter();
+                let item = elems.next().expect("Checked the length above.");
+                for option in elems {
+                    options.parse_option(option, derive)?;
+                }
+                item
+            }
+            Expr::Path(ref path) if derive == Derive::HashBy && path.path.is_ident("_fields") => {
+                return Err(Error::new(
+                    elem.span(),
+                    "`_fields` is not supported by HashBy, the order of the hashed keys doesn't matter",
+                ));
+            }
+            Expr::Path(ref path) if wrapped && path.path.is_ident("_fields") => {
+                return Err(Error::new(
+                    elem.span(),
+                    "`_fields` cannot take options, give them to the fields' attributes instead",
+                ));
+            }
+            Expr::Path(ref path) if wrapped && path.path.is_ident("_variant") => {
+                return Err(Error::new(
+                    elem.span(),
+                    "`_variant` cannot take options, order the variants with `variant_order` instead",
+                ));
+            }
+            Expr::Call(call) if option_name(&call.func).is_some_and(|name| name == "common") => {
+                return Ok(Key {
+                    value: Item::Common(parse_common(call)?),
+                    options,
+                });
+            }
+            Expr::Assign(_) => {
+                let span = elem.span();
+                options.parse_option(elem, derive)?;
+                return Ok(Key {
+                    value: Item::Value(span),
+                    options,
+                });
+            }
+            Expr::Call(_)
+            | Expr::Field(_)
+            | Expr::Path(_)
+            | Expr::MethodCall(_)
+            | Expr::Lit(ExprLit {
+                lit: Lit::Int(_), ..
+            }) => {
+                // TODO: test paths with lengths other than 1
+                return Ok(Key {
+                    value: Item::Expr(elem),
+                    options,
+                });
+            }
+            _ => {
+                return Err(Error::new(elem.span(), format!("Invalid form: `{}`.\nAllowed forms: `field`, `0`, `method()`, `inner.field`, `inner.method()`, `key = |value| ...`", elem.to_token_stream())));
+            }
+        };
+        wrapped = true;
+    }
+}
+
+/// Members of `fields` along with their types.
+fn typed_members(fields: &Fields) -> Vec<(Member, Type)> {
+    fields
+        .members()
+        .zip(fields.iter().map(|field| field.ty.clone()))
+        .collect()
+}
+
+/// Type parameters mentioned by the fields compared as they are, either marked or listed, for each struct or variant in
+/// `field_types`.
+///
+/// Like serde infers its bounds, these are the parameters that need the derived trait, and `PhantomData` fields don't
+/// count. Keys compared through `with`, `key` or a float ordering don't need the derived trait on their type either.
+fn bounded_params(
+    expressions: &[Key<Item>],
+    fields: &ParsedFields,
+    field_types: &[Vec<(Member, Type)>],
+    generics: &Generics,
+) -> Vec<Type> {
+    let is_direct = |options: &KeyOptions| {
+        options.with.is_none()
+            && options.key.is_none()
+            && options.float.is_none()
+            && options.bound.is_none()
+    };
+    let type_of = |types: &[(Member, Type)], member: &Member| {
+        types
+            .iter()
+            .find(|(field, _)| field == member)
+            .map(|(_, ty)| ty.clone())
+    };
+    let mut candidates = Vec::new();
+    match fields {
+        ParsedFields::Struct(keys) => {
+            candidates.extend(
+                keys.iter()
+                    .filter(|key| is_direct(&key.options))
+                    .filter_map(|key| type_of(&field_types[0], &key.value)),
+            );
+            // Fields listed by name or index, like `#[cmp_by(key)]`, are compared as they are too.
+            for key in expressions.iter().filter(|key| is_direct(&key.options)) {
+                if let Item::Expr(expr) = &key.value {
+                    candidates.extend(
+                        FieldPath::parse(expr)
+                            .ok()
+                            .filter(|field| field.path.is_empty())
+                            .and_then(|field| type_of(&field_types[0], &field.member)),
+                    );
+                }
+            }
+        }
+        ParsedFields::Enum(variants) => {
+            for (variant, types) in variants.iter().zip(field_types) {
+                candidates.extend(
+                    variant
+                        .fields
+                        .iter()
+                        .filter(|key| key.value.path.is_empty() && is_direct(&key.options))
+                        .filter_map(|key| type_of(types, &key.value.member)),
+                );
+            }
+            for key in expressions.iter().filter(|key| is_direct(&key.options)) {
+                if let Item::Common(common) = &key.value {
+                    candidates.extend(
+                        field_types
+                            .iter()
+                            .filter_map(|types| type_of(types, &common.member)),
+                    );
+                }
+            }
+        }
+    }
+
+    let type_params = generics
+        .type_params()
+        .map(|param| param.ident.to_string())
+        .collect::<Vec<_>>();
+    let mut bounded: Vec<Type> = Vec::new();
+    for ty in candidates {
+        let is_phantom = matches!(&ty, Type::Path(TypePath { path, .. })
+            if path.segments.last().is_some_and(|segment| segment.ident == "PhantomData"));
+        if is_phantom {
+            continue;
+        }
+        for param in mentioned_params(ty.to_token_stream(), &type_params) {
+            let tokens = param.to_token_stream().to_string();
+            if !bounded
+                .iter()
+                .any(|known| known.to_token_stream().to_string() == tokens)
+            {
+                bounded.push(param);
+            }
+        }
+    }
+    bounded
+}
+
+/// Where predicates given by the `bound` options of the container and of the keys.
+fn given_bounds(
+    expressions: &[Key<Item>],
+    fields: &ParsedFields,
+    container_bound: Option<&[WherePredicate]>,
+) -> Vec<WherePredicate> {
+    let field_options = match fields {
+        ParsedFields::Struct(keys) => keys.iter().map(|key| &key.options).collect::<Vec<_>>(),
+        ParsedFields::Enum(variants) => variants
+            .iter()
+            .flat_map(|variant| &variant.fields)
+            .map(|key| &key.options)
+            .collect(),
+    };
+    container_bound
+        .into_iter()
+        .flatten()
+        .chain(
+            expressions
+                .iter()
+                .map(|key| &key.options)
+                .chain(field_options)
+                .filter_map(|options| options.bound.as_ref())
+                .flatten(),
+        )
+        .cloned()
+        .collect()
+}
+
+/// Type parameters mentioned in `tokens`, or their associated types like `T::Item`.
+fn mentioned_params(tokens: TokenStream, type_params: &[String]) -> Vec<Type> {
+    let tokens = tokens.into_iter().collect::<Vec<_>>();
+    let mut params = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            TokenTree::Ident(ident) if type_params.iter().any(|param| ident == param) => {
+                // A path starting with the parameter names one of its associated types.
+                let mut end = i + 1;
+                while let [TokenTree::Punct(first), TokenTree::Punct(second), TokenTree::Ident(_), ..] =
+                    &tokens[end..]
+                {
+                    if first.as_char() != ':' || second.as_char() != ':' {
+                        break;
+                    }
+                    end += 3;
+                }
+                let path = tokens[i..end].iter().cloned().collect::<TokenStream>();
+                params.push(parse_quote!(#path));
+                i = end;
+                continue;
+            }
+            TokenTree::Group(group) => params.extend(mentioned_params(group.stream(), type_params)),
+            _ => {}
+        }
+        i += 1;
+    }
+    params
+}
+
+/// `generics` with the bound `types: bound` and the given `predicates` added to their where clause, for the impl of the
+/// derived trait `bound`.
+pub fn bounded_generics(
+    generics: &Generics,
+    types: &[Type],
+    predicates: &[WherePredicate],
+    bound: TokenStream,
+) -> Generics {
+    let mut generics = generics.clone();
+    let where_clause = generics.make_where_clause();
+    for ty in types {
+        where_clause.predicates.push(parse_quote!(#ty: #bound));
+    }
+    where_clause.predicates.extend(predicates.iter().cloned());
+    generics
+}
+
+/// Parses `common(field)` or `common(field, required)`.
+fn parse_common(call: ExprCall) -> syn::Result<Common> {
+    let span = call.span();
+    let mut args = call.args.into_iter();
+    let member = match args.next() {
+        Some(Expr::Path(ExprPath {
+            qself: None, path, ..
+        })) if path.get_ident().is_some() => Member::Named(
+            path.get_ident()
+                .expect("Checked by the match guard.")
+                .clone(),
+        ),
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Int(index),
+            ..
+        })) => Member::Unnamed(Index {
+            index: index.base10_parse()?,
+            span: index.span(),
+        }),
+        Some(arg) => {
+            return Err(Error::new(
+                arg.span(),
+                "expected a field name or index: `common(field)`",
+            ))
+        }
+        None => {
+            return Err(Error::new(
+                call.paren_token.span.join(),
+                "expected a field name or index: `common(field)`",
+            ))
+        }
+    };
+    let mut required = false;
+    for option in args {
+        match option_name(&option) {
+            Some(name) if name == "required" && matches!(option, Expr::Path(_)) => {
+                if required {
+                    return Err(Error::new(
+                        name.span(),
+                        "`required` is specified more than once",
+                    ));
+                }
+                required = true;
+            }
+            _ => {
+                return Err(Error::new(
+                    option.span(),
+                    "unknown `common` option, expected `required`",
+                ))
+            }
+        }
+    }
+    Ok(Common {
+        member,
+        required,
+        span,
+        arms: Vec::new(),
+    })
+}
+
+/// Builds the projections of the `common(field)` items, now that the fields of every variant are known.
+fn resolve_common(
+    expressions: &mut [Key<Item>],
+    variants: &[(&ParsedVariant, Vec<Member>)],
+) -> syn::Result<()> {
+    let mut errors = Vec::new();
+    for Key { value, .. } in expressions.iter_mut() {
+        let Item::Common(common) = value else {
+            continue;
+        };
+        let value = format_ident!("value");
+        let mut missing = Vec::new();
+        for (variant, members) in variants {
+            if members.contains(&common.member) {
+                let pattern = variant.member_pattern(&common.member, &value);
+                common.arms.push(if common.required {
+                    quote!(#pattern => #value)
+                } else {
+                    quote!(#pattern => ::core::option::Option::Some(#value))
+                });
+            } else {
+                missing.push(&variant.ident);
+            }
+        }
+        let member = &common.member;
+        if common.arms.is_empty() {
+            errors.push(Error::new(
+                member.span(),
+                format!("no variant has a field `{}`", member.to_token_stream()),
+            ));
+        } else if common.required {
+            errors.extend(missing.into_iter().map(|ident| {
+                Error::new(
+                    member.span(),
+                    format!(
+                        "variant `{ident}` has no field `{}`, which is `required`",
+                        member.to_token_stream()
+                    ),
+                )
+            }));
+        } else if !missing.is_empty() {
+            common.arms.push(quote!(_ => ::core::option::Option::None));
+        }
+    }
+    errors
+        .into_iter()
+        .reduce(|mut acc, err| {
+            acc.combine(err);
+            acc
+        })
+        .map_or(Ok(()), Err)
+}
+
+/// Returns the name of the option if `func` is one that can wrap a container-level item.
+fn modifier(func: &Expr) -> Option<&Ident> {
+    match func {
+        Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) => path
+            .get_ident()
+            .filter(|ident| MODIFIERS.iter().any(|m| ident == m)),
+        _ => None,
+    }
+}
+
+/// Parses the options of a field attribute, which are all optional: `#[cmp_by]` is the same as `#[cmp_by()]`.
+fn parse_field_options(attr: &Attribute, derive: Derive) -> syn::Result<KeyOptions> {
+    let mut options = KeyOptions::default();
+    if !matches!(attr.meta, Meta::Path(_)) {
+        for option in attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)? {
+            options.parse_option(option, derive)?;
+        }
+    }
+    Ok(options)
+}
+
 fn fold_token_errors<T, E>(acc: Result<Vec<T>, E>, res: Result<T, E>) -> Result<Vec<T>, E>
 where
     E: Extend<Error> + IntoIterator<Item = Error>,
@@ -175,24 +1987,25 @@ impl From<Error> for ParsingError {
     }
 }
 
-fn parse_fields(fields: &Fields, attr: &str) -> Result<Vec<Expr>, ParsingError> {
+fn parse_fields(fields: &Fields, derive: Derive) -> Result<Vec<Key<Member>>, ParsingError> {
     // println!("Entered parse_fields");
+    let attr = derive.attr();
     match fields {
         Fields::Named(FieldsNamed { named: fields, .. })
         | Fields::Unnamed(FieldsUnnamed {
             unnamed: fields, ..
-        }) => {
+        }) if !fields.is_empty() => {
             let field_span = fields.span();
             let mut cmp_fields = fields
                 .into_iter()
                 .enumerate()
-                .filter_map(|(i, field)| -> Option<Result<Expr, ParsingError>> {
+                .filter_map(|(i, field)| -> Option<Result<Key<Member>, ParsingError>> {
                     let span = field.span();
                     let mut attrs = field
                         .attrs
                         .iter()
-                        .filter(|i| i.path().get_ident().map_or(false, |i| i == attr));
-                    attrs.next()?;
+                        .filter(|i| i.path().get_ident().is_some_and(|i| i == attr));
+                    let field_attr = attrs.next()?;
                     if attrs.next().is_some() {
                         return Some(Err(ParsingError::Error(Error::new(
                             span,
@@ -200,16 +2013,21 @@ fn parse_fields(fields: &Fields, attr: &str) -> Result<Vec<Expr>, ParsingError>
                         ))));
                     }
                     // println!("Attempting to generate field exprs");
-                    Some(
-                        parse2(if let Some(ident) = &field.ident {
-                            // println!("Generating named field");
-                            ident.to_token_stream()
-                        } else {
-                            // println!("Generating unnamed field");
-                            Index::from(i).to_token_stream()
+                    let options = match parse_field_options(field_attr, derive) {
+                        Ok(options) => options,
+                        Err(err) => return Some(Err(ParsingError::Error(err))),
+                    };
+                    let value = if let Some(ident) = &field.ident {
+                        // println!("Generating named field");
+                        Member::Named(ident.clone())
+                    } else {
+                        // println!("Generating unnamed field");
+                        Member::Unnamed(Index {
+                            index: i as u32,
+                            span,
                         })
-                        .map_err(ParsingError::Error),
-                    )
+                    };
+                    Some(Ok(Key { value, options }))
                 })
                 .peekable();
             if cmp_fields.peek().is_none() {
@@ -217,7 +2035,8 @@ fn parse_fields(fields: &Fields, attr: &str) -> Result<Vec<Expr>, ParsingError>
             }
             cmp_fields.fold(Ok(vec![]), fold_token_errors)
         }
-        Fields::Unit => {
+        // Like unit structs, `struct S {}` and `struct S();` have no field to compare on.
+        _ => {
             // println!("Parsed unit field");
             Ok(Vec::new())
         }
@@ -11,6 +11,7 @@ fn test_cmp_by() {
 
     #[derive(CmpBy, Debug)]
     #[cmp_by(channel(), pitch(), _fields)]
+    #[allow(dead_code, clippy::enum_variant_names)]
     enum Note {
         NoteOn { pitch: u8, channel: u8 },
         NoteOff { pitch: u8, channel: u8 },
@@ -10,6 +10,7 @@ fn ui() {
 #[test]
 fn nightly_ui() {
     let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass_*.rs");
     t.pass("tests/ui/nightly_pass_*.rs");
     t.compile_fail("tests/ui/nightly_fail_*.rs");
 }
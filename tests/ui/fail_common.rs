@@ -0,0 +1,35 @@
+use cmp_by_derive::{CmpBy, HashBy};
+
+#[derive(CmpBy)]
+#[cmp_by(common(channel, required))]
+enum Required {
+    NoteOn { channel: u8 },
+    Clock,
+    Reset,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(common(velocity))]
+enum Missing {
+    NoteOn { channel: u8 },
+}
+
+#[derive(HashBy)]
+#[hash_by(common(channel, optional))]
+enum UnknownOption {
+    NoteOn { channel: u8 },
+}
+
+#[derive(HashBy)]
+#[hash_by(common(channel.id))]
+enum NotAField {
+    NoteOn { channel: u8 },
+}
+
+#[derive(CmpBy)]
+#[cmp_by(common(channel))]
+struct NotAnEnum {
+    channel: u8,
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+use cmp_by_derive::{CmpBy, EqBy};
+
+#[derive(CmpBy)]
+#[cmp_by(discriminant, variant_order = [B, A])]
+enum Listed {
+    A,
+    B,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(discriminant)]
+enum Ranked {
+    #[cmp_by(rank = 1)]
+    A,
+    #[cmp_by(rank = 0)]
+    B,
+}
+
+#[derive(EqBy)]
+#[eq_by(discriminant)]
+enum Equal {
+    A,
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+use cmp_by_derive::HashBy;
+
+#[derive(HashBy)]
+#[hash_by(name(), _fields)]
+struct Container {
+    #[hash_by]
+    id: u32,
+}
+
+#[derive(HashBy)]
+enum Variant {
+    #[hash_by(_fields, seq)]
+    A {
+        #[hash_by]
+        id: u32,
+        seq: u32,
+    },
+}
+
+impl Container {
+    fn name(&self) -> &str {
+        ""
+    }
+}
+
+fn main() {}
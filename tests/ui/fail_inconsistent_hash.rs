@@ -0,0 +1,47 @@
+use cmp_by_derive::{CmpBy, EqBy, HashBy};
+
+#[derive(CmpBy, HashBy)]
+struct NotCompared {
+    #[cmp_by]
+    #[hash_by]
+    id: u32,
+    #[hash_by]
+    name: String,
+}
+
+#[derive(EqBy, HashBy)]
+struct Projected {
+    #[eq_by(key = |s| s.to_lowercase())]
+    #[hash_by]
+    label: String,
+}
+
+#[derive(EqBy, HashBy)]
+struct ComparedWith {
+    #[eq_by(with = eq_ignore_case)]
+    #[hash_by]
+    text: String,
+}
+
+#[derive(CmpBy, HashBy)]
+enum Variant {
+    A {
+        #[cmp_by]
+        id: u32,
+        #[hash_by]
+        seq: u32,
+    },
+}
+
+#[derive(CmpBy)]
+#[cmp_by(allow_inconsistent_hash)]
+struct NotHashBy {
+    #[cmp_by]
+    id: u32,
+}
+
+fn eq_ignore_case(a: &String, b: &String) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+fn main() {}
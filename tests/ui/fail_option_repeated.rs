@@ -0,0 +1,28 @@
+use cmp_by_derive::{CmpBy, HashBy};
+
+#[derive(CmpBy)]
+struct Direction {
+    #[cmp_by(asc, desc)]
+    value: u32,
+}
+
+#[derive(CmpBy)]
+struct FloatOrder {
+    #[cmp_by(total, nan_last)]
+    value: f32,
+}
+
+#[derive(HashBy)]
+struct Key {
+    #[hash_by(key = u32::count_ones, key = u32::leading_zeros)]
+    value: u32,
+}
+
+#[derive(CmpBy)]
+struct Attribute {
+    #[cmp_by]
+    #[cmp_by(desc)]
+    value: u32,
+}
+
+fn main() {}
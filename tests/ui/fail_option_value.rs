@@ -0,0 +1,33 @@
+use cmp_by_derive::{CmpBy, HashBy};
+
+#[derive(CmpBy)]
+struct FlagWithValue {
+    #[cmp_by(desc = true)]
+    value: u32,
+}
+
+#[derive(CmpBy)]
+struct MissingValue {
+    #[cmp_by(with)]
+    value: u32,
+}
+
+#[derive(HashBy)]
+struct MissingKey {
+    #[hash_by(key)]
+    value: u32,
+}
+
+#[derive(CmpBy)]
+struct NotAPath {
+    #[cmp_by(with = "compare")]
+    value: u32,
+}
+
+#[derive(CmpBy)]
+struct NotAnOption {
+    #[cmp_by(1)]
+    value: u32,
+}
+
+fn main() {}
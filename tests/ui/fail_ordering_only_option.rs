@@ -0,0 +1,21 @@
+use cmp_by_derive::{EqBy, HashBy};
+
+#[derive(EqBy)]
+struct Descending {
+    #[eq_by(desc)]
+    value: u32,
+}
+
+#[derive(HashBy)]
+struct NanFirst {
+    #[hash_by(nan_first)]
+    value: f32,
+}
+
+#[derive(HashBy)]
+struct NoneLast {
+    #[hash_by(none_last)]
+    value: Option<u32>,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use cmp_by_derive::{CmpBy, HashBy};
+
+#[derive(CmpBy, HashBy)]
+#[repr(packed)]
+struct Message {
+    #[cmp_by]
+    #[hash_by]
+    id: u32,
+    #[cmp_by]
+    #[hash_by]
+    body: String,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(name.len())]
+#[repr(packed)]
+struct Named {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,37 @@
+use cmp_by_derive::{CmpBy, EqBy, HashBy};
+
+#[derive(CmpBy)]
+struct Misspelled {
+    #[cmp_by(dsc)]
+    value: u32,
+}
+
+#[derive(CmpBy)]
+struct MisspelledValue {
+    #[cmp_by(wiht = compare)]
+    value: u32,
+}
+
+#[derive(HashBy)]
+struct OrderingOnlySuggestion {
+    #[hash_by(totl)]
+    value: f32,
+}
+
+#[derive(EqBy)]
+struct NothingClose {
+    #[eq_by(case_insensitive)]
+    value: String,
+}
+
+#[derive(CmpBy)]
+#[cmp_by((name(), nan_frist))]
+struct ContainerItem {
+    name: f32,
+}
+
+fn compare(a: &u32, b: &u32) -> std::cmp::Ordering {
+    a.cmp(b)
+}
+
+fn main() {}
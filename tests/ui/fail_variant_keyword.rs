@@ -0,0 +1,16 @@
+use cmp_by_derive::CmpBy;
+
+#[derive(CmpBy)]
+#[cmp_by(a, _variant)]
+struct NotAnEnum {
+    a: u8,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(desc(_variant))]
+enum WithOptions {
+    A,
+    B,
+}
+
+fn main() {}
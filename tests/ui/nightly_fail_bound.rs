@@ -0,0 +1,23 @@
+use cmp_by_derive::{CmpBy, HashBy};
+
+#[derive(CmpBy)]
+#[cmp_by(bound = "T Ord")]
+struct Invalid<T> {
+    #[cmp_by]
+    value: T,
+}
+
+#[derive(HashBy)]
+struct NotAString<T> {
+    #[hash_by(bound = T)]
+    value: T,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(bound = "T: Ord", bound = "T: Eq")]
+struct Twice<T> {
+    #[cmp_by]
+    value: T,
+}
+
+fn main() {}
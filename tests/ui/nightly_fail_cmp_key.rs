@@ -0,0 +1,88 @@
+use cmp_by_derive::{CmpBy, EqBy, HashBy, PartialOrdBy};
+
+#[derive(CmpBy)]
+#[cmp_by(cmp_key)]
+enum Enum {
+    #[cmp_by(0)]
+    A(u32),
+}
+
+#[derive(CmpBy)]
+#[cmp_by(cmp_key)]
+struct With {
+    #[cmp_by(with = compare)]
+    value: u32,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(cmp_key)]
+struct Total {
+    #[cmp_by(total)]
+    value: f32,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(cmp_key)]
+struct NoneLast {
+    #[cmp_by(none_last)]
+    value: Option<u32>,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(cmp_key)]
+struct DescNoneFirst {
+    #[cmp_by(desc, none_first)]
+    value: Option<u32>,
+}
+
+#[derive(EqBy)]
+#[eq_by(cmp_key)]
+struct Eq {
+    #[eq_by]
+    value: u32,
+}
+
+#[derive(HashBy)]
+#[hash_by(cmp_key)]
+struct Hash {
+    #[hash_by]
+    value: u32,
+}
+
+#[derive(PartialOrdBy, PartialEq)]
+#[cmp_by(cmp_key)]
+struct PartialOnly {
+    #[cmp_by]
+    value: u32,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(cmp_key = public)]
+struct Visibility {
+    #[cmp_by]
+    value: u32,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(cmp_key, a, b, c, d, e, f, g, h, i, j, k, l, m)]
+struct Wide {
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+    e: u8,
+    f: u8,
+    g: u8,
+    h: u8,
+    i: u8,
+    j: u8,
+    k: u8,
+    l: u8,
+    m: u8,
+}
+
+fn compare(a: &u32, b: &u32) -> std::cmp::Ordering {
+    a.cmp(b)
+}
+
+fn main() {}
@@ -0,0 +1,93 @@
+use cmp_by_derive::{CmpBy, EqBy, HashBy};
+
+#[derive(CmpBy)]
+#[cmp_by(a, b, a)]
+struct Listed {
+    a: u32,
+    b: u32,
+}
+
+#[derive(EqBy)]
+#[eq_by(name(), (inner.id, key = |id| id % 10), (inner.id, key = |id| id % 10))]
+struct ListedWithOptions {
+    inner: Inner,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(a, (a, key = |a| a % 10), desc(a))]
+struct ListedWithOtherOptions {
+    a: u32,
+}
+
+#[derive(HashBy)]
+#[hash_by(a)]
+struct ListedAndMarked {
+    #[hash_by]
+    a: u32,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(desc(0))]
+struct ListedAndMarkedTuple(#[cmp_by(desc)] u32);
+
+#[derive(CmpBy)]
+#[cmp_by((a, key = |a| a % 10), a, (a, key = |a| a % 10))]
+struct ProjectedFirst {
+    a: u32,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(_fields, desc(a))]
+struct MarkedFirst {
+    #[cmp_by]
+    a: u32,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(a, _fields)]
+struct ListedBeforeFields {
+    #[cmp_by]
+    a: u32,
+}
+
+#[derive(HashBy)]
+#[hash_by(a, (a, key = |a| a % 10))]
+struct HashedTwice {
+    a: u32,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(nan_last(x), desc(nan_first(x)))]
+struct SameFloatEquality {
+    x: f32,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(common(id), common(id))]
+enum Common {
+    A { id: u32 },
+    B { id: u32 },
+}
+
+#[derive(CmpBy)]
+enum Variant {
+    #[cmp_by(seq, seq)]
+    A { seq: u32 },
+    #[cmp_by(_fields, id)]
+    B {
+        #[cmp_by]
+        id: u32,
+    },
+}
+
+struct Inner {
+    id: u32,
+}
+
+impl ListedWithOptions {
+    fn name(&self) -> &str {
+        ""
+    }
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+use cmp_by_derive::KeyBy;
+
+#[derive(KeyBy)]
+struct With {
+    #[by(with = compare)]
+    value: u32,
+}
+
+#[derive(KeyBy)]
+#[by(allow_inconsistent_hash)]
+struct Allow {
+    #[by]
+    value: u32,
+}
+
+#[derive(KeyBy)]
+struct Misspelled {
+    #[by(dsc)]
+    value: u32,
+}
+
+#[derive(KeyBy)]
+struct Nothing {
+    value: u32,
+}
+
+fn compare(a: &u32, b: &u32) -> std::cmp::Ordering {
+    a.cmp(b)
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use cmp_by_derive::CmpBy;
+
+#[derive(CmpBy)]
+enum UnknownField {
+    #[cmp_by(velocity)]
+    NoteOn { pitch: u8 },
+}
+
+#[derive(CmpBy)]
+enum NotAField {
+    #[cmp_by(pitch.get())]
+    NoteOn { pitch: Option<u8> },
+}
+
+#[derive(CmpBy)]
+enum VariantKeyword {
+    #[cmp_by(_variant)]
+    NoteOn { pitch: u8 },
+}
+
+fn main() {}
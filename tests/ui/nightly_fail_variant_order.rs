@@ -0,0 +1,35 @@
+use cmp_by_derive::CmpBy;
+
+#[derive(CmpBy)]
+#[cmp_by(variant_order = [C, A, C, D])]
+enum Listed {
+    A,
+    B,
+    C,
+}
+
+#[derive(CmpBy)]
+enum Ranked {
+    #[cmp_by(rank = 1)]
+    A,
+    B,
+    #[cmp_by(rank = 1)]
+    C,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(variant_order = [B, A])]
+enum Both {
+    #[cmp_by(rank = 1)]
+    A,
+    B,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(variant_order = [A])]
+struct NotAnEnum {
+    #[cmp_by]
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,58 @@
+use cmp_by_derive::{CmpBy, EqBy, HashBy};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+#[derive(CmpBy, HashBy)]
+struct Shared<T> {
+    #[cmp_by(bound = "T: Ord")]
+    #[hash_by(bound = "T: Hash")]
+    value: Rc<T>,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(label(), bound = "T: Display")]
+struct Labelled<T> {
+    name: T,
+}
+
+impl<T: Display> Labelled<T> {
+    fn label(&self) -> String {
+        format!("<{}>", self.name)
+    }
+}
+
+/// Neither comparable nor hashable.
+struct Opaque;
+
+#[derive(EqBy)]
+#[eq_by(bound = "")]
+struct Tagged<T> {
+    #[eq_by]
+    tag: u8,
+    #[eq_by(with = always_eq)]
+    payload: T,
+}
+
+fn always_eq<T>(_: &T, _: &T) -> bool {
+    true
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    let shared = |value| Shared {
+        value: Rc::new(value),
+    };
+    assert!(shared(1) < shared(2));
+    assert_eq!(hash(&shared(1)), hash(&1));
+
+    assert!(Labelled { name: 10 } < Labelled { name: 9 });
+
+    assert!(Tagged { tag: 1, payload: Opaque } == Tagged { tag: 1, payload: Opaque });
+}
@@ -0,0 +1,136 @@
+#![deny(warnings)]
+
+use cmp_by_derive::{CmpBy, KeyBy};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+#[derive(CmpBy, Debug)]
+#[cmp_by(cmp_key, desc(priority))]
+pub struct Task {
+    priority: u8,
+    #[cmp_by]
+    name: String,
+    #[cmp_by(key = |tags| tags.len())]
+    tags: Vec<String>,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(cmp_key, len())]
+struct Pair<T>(#[cmp_by] T, #[cmp_by(desc)] T);
+
+impl<T> Pair<T> {
+    fn len(&self) -> usize {
+        2
+    }
+}
+
+#[derive(KeyBy)]
+#[by(cmp_key)]
+struct Version {
+    #[by]
+    major: u32,
+    #[by(none_first)]
+    pre: Option<u32>,
+}
+
+// `Reverse` places `None` last, like `desc` does by default.
+#[derive(CmpBy, Debug)]
+#[cmp_by(cmp_key)]
+struct Ranked {
+    #[cmp_by(desc, none_last)]
+    score: Option<u32>,
+    #[cmp_by(desc)]
+    bonus: Option<u32>,
+}
+
+mod shapes {
+    use cmp_by_derive::CmpBy;
+
+    #[derive(CmpBy)]
+    #[cmp_by(cmp_key = "pub")]
+    pub struct Area {
+        #[cmp_by]
+        pub value: u32,
+    }
+
+    // The method is private by default, so a private key type isn't exposed by a public struct.
+    #[derive(CmpBy)]
+    #[cmp_by(cmp_key)]
+    pub struct Secret {
+        #[cmp_by]
+        key: Hidden,
+    }
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+    struct Hidden(u8);
+
+    pub fn secret(key: u8) -> Secret {
+        let secret = Secret { key: Hidden(key) };
+        assert_eq!(secret.cmp_key(), (&Hidden(key),));
+        secret
+    }
+}
+
+fn main() {
+    assert_eq!(shapes::Area { value: 3 }.cmp_key(), (&3,));
+    assert!(shapes::secret(1) < shapes::secret(2));
+
+    let task = |priority, name: &str| Task {
+        priority,
+        name: name.into(),
+        tags: vec![],
+    };
+    let first = task(2, "b");
+    let (priority, name, _) = first.cmp_key();
+    assert_eq!(priority, Reverse(&2));
+    assert_eq!(name, "b");
+    assert!(first < task(1, "a"));
+
+    let mut tasks = vec![task(1, "a"), task(3, "c"), task(1, "b")];
+    tasks.sort();
+    let names: Vec<_> = tasks.iter().map(|task| task.name.as_str()).collect();
+    assert_eq!(names, ["c", "a", "b"]);
+    tasks.sort_by(|a, b| b.cmp_key().cmp(&a.cmp_key()));
+    let names: Vec<_> = tasks.iter().map(|task| task.name.as_str()).collect();
+    assert_eq!(names, ["b", "a", "c"]);
+
+    let pair = Pair(1, 2);
+    let (_, a, b) = pair.cmp_key();
+    assert_eq!((a, b), (&1, Reverse(&2)));
+    assert!(Pair(1, 2) > Pair(1, 3));
+
+    let versions: BTreeMap<_, _> = [
+        (Version { major: 1, pre: None }, "1.0"),
+        (Version { major: 1, pre: Some(1) }, "1.0-pre.1"),
+    ]
+    .into_iter()
+    .collect();
+    let order: Vec<_> = versions.values().copied().collect();
+    assert_eq!(order, ["1.0", "1.0-pre.1"]);
+    let newest = versions.keys().next_back().unwrap();
+    assert_eq!(newest.cmp_key(), (&1, &Some(1)));
+
+    let ranked = |score, bonus| Ranked { score, bonus };
+    let mut by_ord = vec![
+        ranked(None, Some(1)),
+        ranked(Some(1), None),
+        ranked(Some(2), Some(1)),
+        ranked(Some(1), Some(3)),
+        ranked(None, None),
+    ];
+    let mut by_key: Vec<_> = by_ord.iter().map(|r| ranked(r.score, r.bonus)).collect();
+    by_ord.sort();
+    by_key.sort_by(|a, b| a.cmp_key().cmp(&b.cmp_key()));
+    let order = |ranks: &[Ranked]| ranks.iter().map(|r| (r.score, r.bonus)).collect::<Vec<_>>();
+    assert_eq!(
+        order(&by_ord),
+        [
+            (Some(2), Some(1)),
+            (Some(1), Some(3)),
+            (Some(1), None),
+            (None, Some(1)),
+            (None, None),
+        ]
+    );
+    assert_eq!(order(&by_key), order(&by_ord));
+}
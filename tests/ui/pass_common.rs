@@ -0,0 +1,60 @@
+use cmp_by_derive::{CmpBy, EqBy, HashBy};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(CmpBy, HashBy, Debug)]
+#[cmp_by(common(channel), desc(common(pitch)), _fields)]
+#[hash_by(common(channel), common(pitch))]
+enum Note {
+    NoteOn {
+        channel: u8,
+        pitch: u8,
+    },
+    NoteOff {
+        pitch: u8,
+        channel: u8,
+        #[cmp_by]
+        #[hash_by]
+        velocity: u8,
+    },
+    Clock,
+}
+
+#[derive(EqBy)]
+#[eq_by(common(0, required))]
+enum Owner {
+    User(u32),
+    Group(u32, String),
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    // `None` is less than `Some`.
+    assert!(Note::Clock < Note::NoteOn { channel: 0, pitch: 0 });
+    assert!(Note::NoteOn { channel: 0, pitch: 0 } < Note::NoteOn { channel: 1, pitch: 0 });
+    assert!(Note::NoteOn { channel: 0, pitch: 1 } < Note::NoteOn { channel: 0, pitch: 0 });
+    // Fields are compared across variants before the variants themselves.
+    assert!(
+        Note::NoteOff { channel: 0, pitch: 1, velocity: 0 } < Note::NoteOn { channel: 0, pitch: 0 }
+    );
+    assert!(
+        Note::NoteOff { channel: 0, pitch: 0, velocity: 0 }
+            < Note::NoteOff { channel: 0, pitch: 0, velocity: 1 }
+    );
+
+    let mut hasher = DefaultHasher::new();
+    Some(&3u8).hash(&mut hasher);
+    Some(&60u8).hash(&mut hasher);
+    hasher.write_usize(0);
+    assert_eq!(hash(&Note::NoteOn { channel: 3, pitch: 60 }), hasher.finish());
+
+    assert!(Owner::Group(1, "wheel".into()) == Owner::Group(1, "staff".into()));
+    assert!(Owner::Group(1, "wheel".into()) != Owner::Group(2, "wheel".into()));
+    // Values of different variants still differ.
+    assert!(Owner::User(1) != Owner::Group(1, "wheel".into()));
+}
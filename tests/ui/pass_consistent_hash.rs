@@ -0,0 +1,136 @@
+#![deny(warnings)]
+
+use cmp_by_derive::{CmpBy, EqBy, HashBy, OrdBy, PartialOrdBy};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+/// Hashes fewer keys than it compares, which is consistent.
+#[derive(CmpBy, HashBy)]
+#[cmp_by(name, _fields)]
+#[hash_by(id)]
+struct User {
+    name: String,
+    #[cmp_by]
+    id: u32,
+    #[cmp_by(desc)]
+    age: u8,
+}
+
+/// Equality and hashing both ignore case.
+#[derive(EqBy, HashBy, PartialOrdBy, OrdBy)]
+#[cmp_by(_fields)]
+struct Tag {
+    #[eq_by(key = |s| s.to_lowercase())]
+    #[hash_by(key = |s| s.to_lowercase())]
+    #[cmp_by]
+    label: String,
+}
+
+#[derive(EqBy, HashBy)]
+struct Word {
+    #[eq_by(with = eq_ignore_case)]
+    #[hash_by(with = hash_ignore_case)]
+    text: String,
+}
+
+#[derive(CmpBy, HashBy)]
+enum Shape {
+    Circle {
+        #[cmp_by]
+        #[hash_by]
+        radius: u32,
+    },
+    #[cmp_by(side)]
+    #[hash_by(side)]
+    Square { side: u32 },
+}
+
+/// Hashes a key that equality doesn't test, on purpose.
+#[derive(CmpBy, HashBy)]
+#[hash_by(allow_inconsistent_hash)]
+struct Cached {
+    #[cmp_by]
+    #[hash_by]
+    value: u32,
+    #[hash_by]
+    generation: u32,
+}
+
+/// Hashes a method computed from the tested fields, which is trusted.
+#[derive(EqBy, HashBy)]
+#[hash_by(len())]
+struct Method {
+    #[eq_by]
+    #[hash_by]
+    items: Vec<u32>,
+}
+
+impl Method {
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+fn eq_ignore_case(a: &String, b: &String) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+fn hash_ignore_case<H: Hasher>(value: &String, state: &mut H) {
+    value.to_ascii_lowercase().hash(state)
+}
+
+/// The `#[cmp_by]` keys only give the ordering here, the equality derived by std compares all the fields.
+#[derive(PartialEq, Eq, PartialOrdBy, OrdBy, HashBy)]
+#[hash_by(allow_inconsistent_hash)]
+struct Sample {
+    #[cmp_by]
+    a: u8,
+    #[hash_by]
+    b: u8,
+}
+
+fn main() {
+    let methods: HashSet<_> = [Method { items: vec![1] }, Method { items: vec![1] }]
+        .into_iter()
+        .collect();
+    assert_eq!(methods.len(), 1);
+
+    let samples: HashSet<_> = [Sample { a: 1, b: 2 }, Sample { a: 1, b: 2 }]
+        .into_iter()
+        .collect();
+    assert_eq!(samples.len(), 1);
+    assert!(Sample { a: 1, b: 2 } < Sample { a: 2, b: 1 });
+
+    let tags: HashSet<_> = ["Rust", "rust", "RUST"]
+        .into_iter()
+        .map(|label| Tag {
+            label: label.to_string(),
+        })
+        .collect();
+    assert_eq!(tags.len(), 1);
+
+    let words: HashSet<_> = ["Hello", "hello"]
+        .into_iter()
+        .map(|text| Word {
+            text: text.to_string(),
+        })
+        .collect();
+    assert_eq!(words.len(), 1);
+
+    let users: HashSet<_> = [1, 1, 2]
+        .into_iter()
+        .map(|id| User {
+            name: "Ann".into(),
+            id,
+            age: 30,
+        })
+        .collect();
+    assert_eq!(users.len(), 2);
+
+    let shapes: HashSet<_> = [Shape::Circle { radius: 1 }, Shape::Square { side: 1 }]
+        .into_iter()
+        .collect();
+    assert_eq!(shapes.len(), 2);
+
+    assert!(Cached { value: 1, generation: 1 } == Cached { value: 1, generation: 2 });
+}
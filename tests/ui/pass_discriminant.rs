@@ -0,0 +1,56 @@
+use cmp_by_derive::{CmpBy, HashBy};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const CONTROL: u8 = 0xB0;
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by(discriminant)]
+#[hash_by(discriminant)]
+#[repr(u8)]
+enum Status {
+    ControlChange {
+        #[cmp_by]
+        #[hash_by]
+        controller: u8,
+    } = CONTROL,
+    NoteOff(#[cmp_by] #[hash_by] u8) = 0x80,
+    NoteOn(#[cmp_by] #[hash_by] u8),
+    PitchBend = 0xE0,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(_variant, discriminant, priority())]
+#[repr(i64)]
+enum Wide {
+    Low = -0x80,
+    High = 0xFF_FFFF_FFFF,
+    Next,
+}
+
+impl Wide {
+    fn priority(&self) -> u8 {
+        0
+    }
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    assert!(Status::NoteOff(100) < Status::NoteOn(0));
+    assert!(Status::NoteOn(100) < Status::ControlChange { controller: 0 });
+    assert!(Status::ControlChange { controller: 100 } < Status::PitchBend);
+    assert!(Status::NoteOn(1) < Status::NoteOn(2));
+
+    let mut hasher = DefaultHasher::new();
+    0x81u8.hash(&mut hasher);
+    5u8.hash(&mut hasher);
+    assert_eq!(hash(&Status::NoteOn(5)), hasher.finish());
+
+    assert!(Wide::Low < Wide::High);
+    assert!(Wide::High < Wide::Next);
+}
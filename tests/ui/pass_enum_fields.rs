@@ -0,0 +1,75 @@
+use cmp_by_derive::{CmpBy, HashBy};
+use core::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(CmpBy, HashBy)]
+enum Tuple {
+    A(#[cmp_by] #[hash_by] u8, f32),
+    B(f32, #[cmp_by] #[hash_by] u16, #[cmp_by] #[hash_by] u32),
+}
+
+#[derive(CmpBy, HashBy)]
+enum Named {
+    A {
+        #[cmp_by]
+        #[hash_by]
+        a: u8,
+        ignored: f32,
+    },
+    B {
+        ignored: f32,
+        #[cmp_by]
+        #[hash_by]
+        b: u16,
+    },
+}
+
+#[derive(CmpBy, HashBy)]
+enum Mixed {
+    Tuple(f32, #[cmp_by] #[hash_by] u8),
+    Named {
+        #[cmp_by]
+        #[hash_by]
+        other: u8,
+        ignored: f32,
+    },
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    assert_eq!(Tuple::A(1, 0.0).cmp(&Tuple::A(1, 1.0)), Ordering::Equal);
+    assert_eq!(Tuple::A(2, 0.0).cmp(&Tuple::A(1, 1.0)), Ordering::Greater);
+    assert_eq!(Tuple::B(0.0, 1, 2).cmp(&Tuple::B(1.0, 1, 3)), Ordering::Less);
+    assert_eq!(Tuple::A(9, 0.0).cmp(&Tuple::B(0.0, 0, 0)), Ordering::Less);
+    assert_eq!(hash(&Tuple::B(0.0, 1, 2)), hash(&Tuple::B(1.0, 1, 2)));
+
+    assert_eq!(
+        Named::A { a: 1, ignored: 0.0 }.cmp(&Named::A { a: 1, ignored: 1.0 }),
+        Ordering::Equal
+    );
+    assert_eq!(
+        Named::B { ignored: 0.0, b: 3 }.cmp(&Named::B { ignored: 0.0, b: 2 }),
+        Ordering::Greater
+    );
+    assert_eq!(
+        hash(&Named::A { a: 1, ignored: 0.0 }),
+        hash(&Named::A { a: 1, ignored: 1.0 })
+    );
+
+    assert_eq!(Mixed::Tuple(0.0, 1).cmp(&Mixed::Tuple(1.0, 2)), Ordering::Less);
+    assert_eq!(
+        Mixed::Named { other: 3, ignored: 0.0 }.cmp(&Mixed::Named { other: 3, ignored: 1.0 }),
+        Ordering::Equal
+    );
+    assert_eq!(
+        Mixed::Named { other: 0, ignored: 0.0 }.cmp(&Mixed::Tuple(0.0, 0)),
+        Ordering::Greater
+    );
+    assert_eq!(hash(&Mixed::Tuple(0.0, 1)), hash(&Mixed::Tuple(1.0, 1)));
+}
@@ -0,0 +1,54 @@
+use cmp_by_derive::{EqBy, HashBy};
+use std::collections::{HashMap, HashSet};
+
+#[derive(EqBy, HashBy)]
+struct Reading {
+    #[eq_by]
+    #[hash_by]
+    sensor: u32,
+    #[eq_by(total)]
+    #[hash_by(total)]
+    value: f64,
+    tags: HashMap<String, String>,
+}
+
+#[derive(EqBy)]
+#[eq_by(_fields, key = |e: &Event| e.labels().len())]
+enum Event {
+    Sample(#[eq_by] HashSet<u8>, f32),
+    Tick,
+    Named {
+        #[eq_by(key = str::len)]
+        name: String,
+        labels: Vec<f32>,
+    },
+}
+
+impl Event {
+    fn labels(&self) -> &[f32] {
+        &[]
+    }
+}
+
+fn main() {
+    let reading = |sensor, value| Reading {
+        sensor,
+        value,
+        tags: HashMap::new(),
+    };
+    let mut set = HashSet::new();
+    set.insert(reading(1, f64::NAN));
+    assert!(set.contains(&reading(1, f64::NAN)));
+    assert!(!set.contains(&reading(2, f64::NAN)));
+    assert!(reading(1, -0.0) != reading(1, 0.0));
+    assert_eq!(f64::NAN.total_cmp(&-f64::NAN).is_eq(), reading(1, f64::NAN) == reading(1, -f64::NAN));
+    assert!(reading(1, 1.0) != reading(1, 2.0));
+
+    assert!(Event::Tick == Event::Tick);
+    assert!(Event::Sample(HashSet::from([1]), 0.0) == Event::Sample(HashSet::from([1]), 1.0));
+    assert!(Event::Sample(HashSet::new(), 0.0) != Event::Tick);
+    assert!(
+        Event::Named { name: "ab".into(), labels: vec![] }
+            == Event::Named { name: "cd".into(), labels: vec![] }
+    );
+}
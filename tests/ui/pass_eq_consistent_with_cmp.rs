@@ -0,0 +1,63 @@
+use cmp_by_derive::CmpBy;
+use core::cmp::Ordering;
+
+#[derive(CmpBy, Clone)]
+#[cmp_by(desc(len()), _fields)]
+struct Record {
+    #[cmp_by(nan_last)]
+    ratio: f32,
+    #[cmp_by(none_first, total)]
+    weight: Option<f64>,
+    #[cmp_by(with = cmp_ci)]
+    name: String,
+}
+
+impl Record {
+    fn len(&self) -> usize {
+        self.name.len()
+    }
+}
+
+fn cmp_ci(a: &String, b: &String) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+#[derive(CmpBy)]
+struct Single {
+    #[cmp_by(desc)]
+    value: u8,
+    other: f32,
+}
+
+fn check<T: Ord>(values: &[T]) {
+    for a in values {
+        for b in values {
+            let ordering = a.cmp(b);
+            assert_eq!(a == b, ordering.is_eq());
+            assert_eq!(a < b, ordering.is_lt());
+            assert_eq!(a <= b, ordering.is_le());
+            assert_eq!(a > b, ordering.is_gt());
+            assert_eq!(a >= b, ordering.is_ge());
+        }
+    }
+}
+
+fn main() {
+    let record = |ratio, weight, name: &str| Record {
+        ratio,
+        weight,
+        name: name.into(),
+    };
+    check(&[
+        record(f32::NAN, None, "a"),
+        record(f32::NAN, Some(-0.0), "A"),
+        record(1.0, Some(0.0), "a"),
+        record(1.0, Some(f64::NAN), "ab"),
+        record(2.0, None, "b"),
+    ]);
+    check(&[
+        Single { value: 1, other: 0.0 },
+        Single { value: 1, other: 1.0 },
+        Single { value: 2, other: 0.0 },
+    ]);
+}
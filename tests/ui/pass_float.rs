@@ -0,0 +1,80 @@
+use cmp_by_derive::{CmpBy, HashBy};
+use core::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(CmpBy, HashBy)]
+struct Total {
+    #[cmp_by(total)]
+    #[hash_by(total)]
+    value: f64,
+}
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by(nan_last(ratio()))]
+#[hash_by(total(ratio()))]
+struct Ratio(f32, f32);
+
+impl Ratio {
+    fn ratio(&self) -> f32 {
+        self.0 / self.1
+    }
+}
+
+// `total` tells apart the values that `nan_last` finds equal.
+#[derive(CmpBy)]
+#[cmp_by(nan_last(x), total(x))]
+struct Signed {
+    x: f32,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(_fields, desc(nan_last(value())))]
+enum Sample {
+    Measured(#[cmp_by(nan_first)] f32),
+    Missing,
+}
+
+impl Sample {
+    fn value(&self) -> f32 {
+        match self {
+            Sample::Measured(value) => *value,
+            Sample::Missing => f32::NAN,
+        }
+    }
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    assert_eq!(Signed { x: -0.0 }.cmp(&Signed { x: 0.0 }), Ordering::Less);
+    assert_eq!(Signed { x: 1.0 }.cmp(&Signed { x: f32::NAN }), Ordering::Less);
+    let total = |value| Total { value };
+    assert_eq!(total(-0.0).cmp(&total(0.0)), Ordering::Less);
+    assert_eq!(total(f64::NAN).cmp(&total(f64::INFINITY)), Ordering::Greater);
+    assert_eq!(total(f64::NAN).cmp(&total(f64::NAN)), Ordering::Equal);
+    assert_eq!(hash(&total(-0.0)), hash(&total(0.0)));
+    assert_eq!(hash(&total(f64::NAN)), hash(&total(-f64::NAN)));
+    assert_ne!(hash(&total(1.0)), hash(&total(2.0)));
+
+    assert_eq!(Ratio(0.0, 0.0).cmp(&Ratio(1.0, 0.0)), Ordering::Greater);
+    assert_eq!(Ratio(0.0, 0.0).cmp(&Ratio(0.0, 0.0)), Ordering::Equal);
+    assert_eq!(Ratio(1.0, 2.0).cmp(&Ratio(1.0, 4.0)), Ordering::Greater);
+    assert_eq!(Ratio(-0.0, 1.0).cmp(&Ratio(0.0, 1.0)), Ordering::Equal);
+    assert_eq!(hash(&Ratio(-0.0, 1.0)), hash(&Ratio(0.0, 1.0)));
+
+    assert_eq!(
+        Sample::Measured(f32::NAN).cmp(&Sample::Measured(f32::NEG_INFINITY)),
+        Ordering::Less
+    );
+    assert_eq!(
+        Sample::Measured(1.0).cmp(&Sample::Measured(2.0)),
+        Ordering::Less
+    );
+    assert_eq!(Sample::Measured(f32::NAN).cmp(&Sample::Missing), Ordering::Less);
+    assert_eq!(Sample::Missing.cmp(&Sample::Missing), Ordering::Equal);
+}
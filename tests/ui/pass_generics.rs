@@ -0,0 +1,85 @@
+use cmp_by_derive::{CmpBy, HashBy};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+#[derive(CmpBy, HashBy)]
+struct Defaulted<T: Ord + Hash = u8> {
+    #[cmp_by]
+    #[hash_by]
+    value: T,
+}
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by(len())]
+#[hash_by(len())]
+struct Buffer<const N: usize = 4> {
+    data: [u8; N],
+}
+
+impl<const N: usize> Buffer<N> {
+    fn len(&self) -> usize {
+        self.data.iter().filter(|&&b| b != 0).count()
+    }
+}
+
+#[derive(CmpBy, HashBy)]
+#[hash_by(key())]
+enum Entry<'a, K, V: ?Sized = str, const N: usize = 1>
+where
+    K: Ord + Hash + Debug + 'a,
+    for<'b> &'b V: PartialEq,
+    [u8; N]: Sized,
+{
+    Borrowed {
+        #[cmp_by]
+        key: &'a K,
+        value: &'a V,
+    },
+    Owned(#[cmp_by] K, [u8; N]),
+}
+
+impl<'a, K, V: ?Sized, const N: usize> Entry<'a, K, V, N>
+where
+    K: Ord + Hash + Debug + 'a,
+    for<'b> &'b V: PartialEq,
+    [u8; N]: Sized,
+{
+    fn key(&self) -> &K {
+        match self {
+            Entry::Borrowed { key, .. } => key,
+            Entry::Owned(key, _) => key,
+        }
+    }
+}
+
+#[derive(CmpBy)]
+struct Attributed<#[allow(unused)] T: Ord, #[allow(unused)] const N: usize> {
+    #[cmp_by]
+    values: [T; N],
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    let small: Defaulted = Defaulted { value: 1 };
+    assert!(small < Defaulted { value: 2 });
+    assert_eq!(hash(&small), hash(&1u8));
+
+    let buffer: Buffer = Buffer { data: [1, 0, 0, 0] };
+    assert!(buffer < Buffer { data: [1, 1, 0, 0] });
+    assert_eq!(hash(&buffer), hash(&1usize));
+
+    let key = 3;
+    let borrowed: Entry<'_, i32> = Entry::Borrowed { key: &key, value: "three" };
+    assert!(borrowed < Entry::Owned(0, [0]));
+    assert!(Entry::<i32>::Owned(1, [0]) < Entry::Owned(2, [0]));
+    let other: Entry<'_, i32> = Entry::Borrowed { key: &key, value: "trois" };
+    assert_eq!(hash(&borrowed), hash(&other));
+
+    assert!(Attributed { values: [1, 2] } < Attributed { values: [1, 3] });
+}
@@ -0,0 +1,46 @@
+use cmp_by_derive::{CmpBy, HashBy};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(CmpBy, HashBy)]
+enum Message {
+    Data(#[cmp_by] #[hash_by] u32, f32),
+    Ping,
+    Pong,
+    Unsupported { raw: Vec<u8> },
+}
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by(priority())]
+#[hash_by(priority())]
+enum Unmarked {
+    Low,
+    High(f32),
+}
+
+impl Unmarked {
+    fn priority(&self) -> u8 {
+        match self {
+            Unmarked::Low => 0,
+            Unmarked::High(_) => 1,
+        }
+    }
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    assert_eq!(hash(&Message::Ping), hash(&Message::Ping));
+    assert_ne!(hash(&Message::Ping), hash(&Message::Pong));
+    assert_eq!(hash(&Message::Data(1, 0.0)), hash(&Message::Data(1, 1.0)));
+    assert_eq!(
+        hash(&Message::Unsupported { raw: vec![1] }),
+        hash(&Message::Unsupported { raw: vec![2] })
+    );
+    assert_eq!(hash(&Unmarked::High(0.0)), hash(&Unmarked::High(1.0)));
+    assert_ne!(hash(&Unmarked::Low), hash(&Unmarked::High(1.0)));
+}
@@ -0,0 +1,87 @@
+use cmp_by_derive::{CmpBy, EqBy, HashBy, PartialOrdBy};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashSet};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Neither ordered nor hashable.
+struct Opaque;
+
+#[derive(CmpBy, HashBy)]
+struct Wrapper<T, U> {
+    #[cmp_by]
+    #[hash_by]
+    key: T,
+    payload: Vec<U>,
+    #[cmp_by]
+    #[hash_by]
+    marker: PhantomData<U>,
+}
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by(key)]
+#[hash_by(key)]
+struct Listed<T, U> {
+    key: T,
+    payload: U,
+}
+
+#[derive(CmpBy)]
+#[cmp_by(desc(0))]
+struct Pair<T, U>(T, U);
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by(common(id))]
+#[hash_by(common(id))]
+enum Node<K, V> {
+    Leaf { id: K, value: V },
+    Branch { id: K, children: Vec<Node<K, V>> },
+    Empty,
+}
+
+#[derive(EqBy)]
+struct Labelled<L> {
+    #[eq_by]
+    label: L,
+}
+
+#[derive(PartialOrdBy, PartialEq)]
+struct Ranked<R: PartialEq> {
+    #[cmp_by]
+    rank: R,
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    let wrapper = |key| Wrapper {
+        key,
+        payload: vec![Opaque],
+        marker: PhantomData::<Opaque>,
+    };
+    assert!(wrapper(1) < wrapper(2));
+    let set: BTreeSet<_> = [wrapper(2), wrapper(1)].into_iter().collect();
+    assert_eq!(set.len(), 2);
+    assert_eq!(hash(&wrapper(1)), hash(&wrapper(1)));
+
+    let listed = |key| Listed {
+        key,
+        payload: Opaque,
+    };
+    assert!(listed(1) < listed(2));
+    assert_eq!(hash(&listed(1)), hash(&listed(1)));
+    assert!(Pair(2, Opaque) < Pair(1, Opaque));
+
+    let leaf = |id| Node::Leaf { id, value: Opaque };
+    assert!(leaf(1) < Node::Branch { id: 2, children: vec![] });
+    assert!(Node::<u8, Opaque>::Empty < leaf(0));
+    let set: HashSet<_> = [leaf(1), leaf(1), leaf(2)].into_iter().collect();
+    assert_eq!(set.len(), 2);
+
+    assert!(Labelled { label: "a" } == Labelled { label: "a" });
+    assert!(Ranked { rank: 1 } < Ranked { rank: 2 });
+}
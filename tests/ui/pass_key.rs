@@ -0,0 +1,92 @@
+use cmp_by_derive::{CmpBy, HashBy};
+use core::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by(key = |s| s.name.len(), desc(key = |s| &s.name))]
+#[hash_by(key = |s| s.name.len())]
+struct Person {
+    name: String,
+}
+
+#[derive(CmpBy, HashBy)]
+struct Word {
+    #[cmp_by(key = str::len)]
+    #[hash_by(key = str::len)]
+    text: String,
+    #[cmp_by(key = |v| v.first())]
+    letters: Vec<char>,
+}
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by((tags(), key = |t| t.len()))]
+enum Entry {
+    Text(
+        #[cmp_by(key = |s| s.to_lowercase())]
+        #[hash_by(key = |s| s.to_lowercase())]
+        String,
+    ),
+    Number {
+        #[cmp_by(key = |n| n.abs())]
+        #[hash_by(key = |n| n.abs())]
+        value: i32,
+    },
+}
+
+impl Entry {
+    fn tags(&self) -> Vec<&str> {
+        Vec::new()
+    }
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+// The marked field tells apart the values its listed projection doesn't.
+#[derive(CmpBy)]
+#[cmp_by((digits, key = |d| d % 10))]
+struct Digits {
+    #[cmp_by]
+    digits: u32,
+}
+
+fn main() {
+    assert!(Digits { digits: 21 } < Digits { digits: 12 });
+    assert!(Digits { digits: 12 } < Digits { digits: 22 });
+
+    let person = |name: &str| Person {
+        name: name.to_string(),
+    };
+    assert_eq!(person("ab").cmp(&person("abc")), Ordering::Less);
+    assert_eq!(person("ab").cmp(&person("ba")), Ordering::Greater);
+    assert_eq!(hash(&person("ab")), hash(&person("ba")));
+
+    let word = |text: &str, letters: &[char]| Word {
+        text: text.to_string(),
+        letters: letters.to_vec(),
+    };
+    assert_eq!(word("ab", &['a']).cmp(&word("cd", &['a', 'z'])), Ordering::Equal);
+    assert_eq!(word("ab", &[]).cmp(&word("cd", &['a'])), Ordering::Less);
+    assert_eq!(hash(&word("ab", &[])), hash(&word("cd", &['a'])));
+
+    assert_eq!(
+        Entry::Text("ABC".to_string()).cmp(&Entry::Text("abc".to_string())),
+        Ordering::Equal
+    );
+    assert_eq!(
+        hash(&Entry::Text("ABC".to_string())),
+        hash(&Entry::Text("abc".to_string()))
+    );
+    assert_eq!(
+        Entry::Number { value: -2 }.cmp(&Entry::Number { value: 1 }),
+        Ordering::Greater
+    );
+    assert_eq!(
+        hash(&Entry::Number { value: -2 }),
+        hash(&Entry::Number { value: 2 })
+    );
+}
@@ -0,0 +1,80 @@
+#![deny(warnings)]
+
+use cmp_by_derive::KeyBy;
+use std::collections::{BTreeSet, HashSet};
+
+#[derive(KeyBy, Debug)]
+#[by(desc(priority), _fields)]
+struct Job<T> {
+    priority: u8,
+    #[by(key = |name| name.to_lowercase())]
+    name: String,
+    #[by(nan_last)]
+    cost: f64,
+    payload: T,
+}
+
+#[derive(KeyBy, Debug)]
+#[by(variant_order = [Circle, Square], common(id))]
+enum Shape {
+    Circle {
+        id: u32,
+        #[by(total)]
+        radius: f32,
+    },
+    #[by(side.len)]
+    Square { id: u32, side: Side },
+}
+
+#[derive(Debug)]
+struct Side {
+    len: u32,
+}
+
+#[derive(KeyBy, Debug)]
+struct Unit;
+
+fn main() {
+    let job = |priority, name: &str, cost| Job {
+        priority,
+        name: name.into(),
+        cost,
+        payload: (),
+    };
+    assert!(job(2, "b", 1.0) < job(1, "a", 1.0));
+    assert!(job(1, "a", 1.0) < job(1, "B", 1.0));
+    assert!(job(1, "a", 1.0) < job(1, "a", f64::NAN));
+    assert_eq!(job(1, "Build", 1.0), job(1, "build", 1.0));
+
+    let jobs: HashSet<_> = [job(1, "Build", f64::NAN), job(1, "build", f64::NAN)]
+        .into_iter()
+        .collect();
+    assert_eq!(jobs.len(), 1);
+    let jobs: HashSet<_> = [job(1, "a", 0.0), job(1, "a", -0.0)].into_iter().collect();
+    assert_eq!(jobs.len(), 1);
+
+    let shapes: BTreeSet<_> = [
+        Shape::Square { id: 1, side: Side { len: 2 } },
+        Shape::Circle { id: 1, radius: 1.0 },
+        Shape::Circle { id: 0, radius: 3.0 },
+    ]
+    .into_iter()
+    .collect();
+    let ids: Vec<_> = shapes
+        .iter()
+        .map(|shape| match shape {
+            Shape::Circle { id, .. } | Shape::Square { id, .. } => *id,
+        })
+        .collect();
+    assert_eq!(ids, [0, 1, 1]);
+    let shapes: HashSet<_> = [
+        Shape::Square { id: 1, side: Side { len: 2 } },
+        Shape::Square { id: 1, side: Side { len: 2 } },
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(shapes.len(), 1);
+
+    assert_eq!(Unit, Unit);
+    assert_eq!(HashSet::from([Unit, Unit]).len(), 1);
+}
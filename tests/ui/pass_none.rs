@@ -0,0 +1,48 @@
+use cmp_by_derive::CmpBy;
+use core::cmp::Ordering;
+
+#[derive(CmpBy)]
+#[cmp_by(none_last(score()), desc(none_last(rank())))]
+struct Report {
+    score: Option<u32>,
+    rank: Option<u8>,
+}
+
+impl Report {
+    fn score(&self) -> Option<u32> {
+        self.score
+    }
+
+    fn rank(&self) -> Option<&u8> {
+        self.rank.as_ref()
+    }
+}
+
+#[derive(CmpBy)]
+enum Reading {
+    Value(#[cmp_by(none_first, desc, nan_last)] Option<f32>),
+    Named {
+        #[cmp_by(none_last, key = |s| s.as_deref())]
+        name: Option<String>,
+    },
+}
+
+fn main() {
+    let report = |score, rank| Report { score, rank };
+    assert_eq!(report(None, None).cmp(&report(Some(0), None)), Ordering::Greater);
+    assert_eq!(report(Some(1), None).cmp(&report(Some(0), None)), Ordering::Greater);
+    assert_eq!(report(None, None).cmp(&report(None, None)), Ordering::Equal);
+    assert_eq!(report(None, None).cmp(&report(None, Some(1))), Ordering::Greater);
+    assert_eq!(report(None, Some(2)).cmp(&report(None, Some(1))), Ordering::Less);
+
+    assert_eq!(Reading::Value(None).cmp(&Reading::Value(Some(f32::NAN))), Ordering::Less);
+    assert_eq!(Reading::Value(Some(1.0)).cmp(&Reading::Value(Some(2.0))), Ordering::Greater);
+    assert_eq!(
+        Reading::Value(Some(f32::NAN)).cmp(&Reading::Value(Some(2.0))),
+        Ordering::Greater
+    );
+    assert_eq!(
+        Reading::Named { name: None }.cmp(&Reading::Named { name: Some("a".into()) }),
+        Ordering::Greater
+    );
+}
@@ -0,0 +1,103 @@
+#![deny(warnings)]
+
+use cmp_by_derive::{CmpBy, EqBy, HashBy};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(CmpBy, HashBy, Clone, Copy)]
+#[cmp_by(version, desc(flags), _fields)]
+#[hash_by(version, flags)]
+#[repr(C, packed)]
+struct Header {
+    version: u8,
+    flags: u16,
+    #[cmp_by]
+    #[hash_by]
+    length: u32,
+    #[cmp_by(total)]
+    weight: f64,
+    checksum: u32,
+}
+
+#[derive(EqBy)]
+#[eq_by(header.version)]
+#[repr(packed(2))]
+struct Frame {
+    header: Header,
+    #[eq_by(key = |sequence| sequence % 256)]
+    sequence: u64,
+}
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by(inner.get(), inner.value.count_ones(), name())]
+#[hash_by(inner.get())]
+#[repr(packed)]
+struct Wrapped {
+    tag: u8,
+    inner: Inner,
+}
+
+#[derive(Clone, Copy)]
+struct Inner {
+    value: u32,
+}
+
+impl Inner {
+    fn get(&self) -> u32 {
+        self.value
+    }
+}
+
+impl Wrapped {
+    fn name(&self) -> u8 {
+        self.tag
+    }
+}
+
+#[derive(CmpBy)]
+#[repr(packed)]
+struct Single {
+    #[cmp_by]
+    value: i32,
+    _padding: u8,
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    let header = |version, flags, length, checksum| Header {
+        version,
+        flags,
+        length,
+        weight: 1.5,
+        checksum,
+    };
+    assert!(header(1, 0, 9, 0) < header(2, 0, 0, 0));
+    assert!(header(1, 1, 0, 0) < header(1, 0, 0, 0));
+    assert!(header(1, 0, 1, 0) < header(1, 0, 2, 0));
+    assert!(header(1, 0, 1, 0) == header(1, 0, 1, 7));
+    assert_eq!(hash(&header(1, 2, 3, 4)), hash(&header(1, 2, 3, 5)));
+    assert_ne!(hash(&header(1, 2, 3, 4)), hash(&header(1, 2, 4, 4)));
+
+    let frame = |version, sequence| Frame {
+        header: header(version, 0, 0, 0),
+        sequence,
+    };
+    assert!(frame(1, 1) == frame(1, 257));
+    assert!(frame(1, 1) != frame(2, 1));
+
+    let wrapped = |tag, value| Wrapped {
+        tag,
+        inner: Inner { value },
+    };
+    assert!(wrapped(9, 1) < wrapped(0, 2));
+    assert!(wrapped(0, 1) < wrapped(1, 1));
+    assert_eq!(hash(&wrapped(0, 1)), hash(&wrapped(1, 1)));
+
+    assert!(Single { value: 1, _padding: 0 } < Single { value: 2, _padding: 0 });
+    assert!(Single { value: 1, _padding: 0 } <= Single { value: 1, _padding: 1 });
+}
@@ -0,0 +1,43 @@
+use cmp_by_derive::CmpBy;
+use core::cmp::Ordering;
+
+#[derive(CmpBy)]
+#[cmp_by(desc(score()), name)]
+struct Player {
+    name: String,
+    points: Vec<u32>,
+}
+
+impl Player {
+    fn score(&self) -> u32 {
+        self.points.iter().sum()
+    }
+}
+
+#[derive(CmpBy)]
+struct Fields(#[cmp_by(rev)] u8, #[cmp_by(asc)] u8, f32);
+
+#[derive(CmpBy)]
+enum Variants {
+    A(#[cmp_by(desc)] u8),
+    B {
+        #[cmp_by(desc)]
+        b: u8,
+    },
+}
+
+fn main() {
+    let player = |name: &str, points: &[u32]| Player {
+        name: name.to_string(),
+        points: points.to_vec(),
+    };
+    assert_eq!(player("a", &[1]).cmp(&player("b", &[2])), Ordering::Greater);
+    assert_eq!(player("a", &[2]).cmp(&player("b", &[1, 1])), Ordering::Less);
+
+    assert_eq!(Fields(1, 0, 0.0).cmp(&Fields(2, 0, 0.0)), Ordering::Greater);
+    assert_eq!(Fields(1, 0, 0.0).cmp(&Fields(1, 1, 0.0)), Ordering::Less);
+
+    assert_eq!(Variants::A(1).cmp(&Variants::A(2)), Ordering::Greater);
+    assert_eq!(Variants::B { b: 2 }.cmp(&Variants::B { b: 1 }), Ordering::Less);
+    assert_eq!(Variants::A(0).cmp(&Variants::B { b: 0 }), Ordering::Less);
+}
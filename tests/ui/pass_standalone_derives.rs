@@ -0,0 +1,49 @@
+use cmp_by_derive::{EqOnlyBy, OrdBy, PartialEqBy, PartialOrdBy};
+use std::cmp::Ordering;
+
+#[derive(PartialOrdBy, OrdBy, EqOnlyBy)]
+struct Versioned {
+    #[cmp_by]
+    version: u32,
+    label: &'static str,
+}
+
+// Hand-written equality that agrees with the derived ordering.
+impl PartialEq for Versioned {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+    }
+}
+
+#[derive(PartialEqBy)]
+#[cmp_by(name())]
+struct Named {
+    name: String,
+    payload: f32,
+}
+
+impl Named {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[derive(PartialEqBy, PartialOrdBy)]
+enum Shape {
+    Circle(#[cmp_by] u32),
+    Square { #[cmp_by(desc)] side: u32 },
+}
+
+fn main() {
+    let a = Versioned { version: 1, label: "a" };
+    let b = Versioned { version: 2, label: "b" };
+    assert_eq!(a.cmp(&b), Ordering::Less);
+    assert!(a < b);
+    assert_ne!(a.label, b.label);
+
+    assert!(Named { name: "x".into(), payload: 0.0 } == Named { name: "x".into(), payload: 1.0 });
+
+    assert!(Shape::Circle(3) < Shape::Square { side: 1 });
+    assert!(Shape::Square { side: 2 } < Shape::Square { side: 1 });
+    assert!(Shape::Circle(1) == Shape::Circle(1));
+}
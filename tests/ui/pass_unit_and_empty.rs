@@ -0,0 +1,60 @@
+#![deny(warnings)]
+
+use cmp_by_derive::{CmpBy, EqBy, HashBy, PartialEqBy, PartialOrdBy};
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(CmpBy, HashBy, Debug)]
+struct Marker;
+
+#[derive(EqBy)]
+struct Placeholder;
+
+#[derive(CmpBy, HashBy, Debug)]
+struct Braced {}
+
+#[derive(CmpBy, HashBy, Debug)]
+struct Parenthesized();
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by(name())]
+#[hash_by(name())]
+struct Named;
+
+impl Named {
+    fn name(&self) -> &str {
+        "named"
+    }
+}
+
+#[derive(CmpBy, HashBy)]
+#[allow(dead_code)]
+enum Never {}
+
+#[derive(EqBy)]
+#[allow(dead_code)]
+enum Nothing {}
+
+#[derive(PartialEqBy, PartialOrdBy)]
+#[allow(dead_code)]
+enum Void {}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    assert_eq!(Marker.cmp(&Marker), Ordering::Equal);
+    assert_eq!(Marker, Marker);
+    assert_eq!(hash(&Marker), DefaultHasher::new().finish());
+    assert!(Placeholder == Placeholder);
+    assert_eq!(Braced {}.cmp(&Braced {}), Ordering::Equal);
+    assert_eq!(hash(&Braced {}), DefaultHasher::new().finish());
+    assert_eq!(Parenthesized(), Parenthesized());
+    assert_eq!(hash(&Parenthesized()), DefaultHasher::new().finish());
+    assert!(Named <= Named);
+    assert_eq!(hash(&Named), hash(&"named"));
+}
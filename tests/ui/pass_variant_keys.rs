@@ -0,0 +1,71 @@
+use cmp_by_derive::{CmpBy, EqBy, HashBy};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Header {
+    seq: u32,
+    id: u8,
+}
+
+#[derive(CmpBy, HashBy)]
+enum Message {
+    #[cmp_by(velocity, desc(pitch))]
+    #[hash_by(velocity, pitch)]
+    NoteOn { pitch: u8, velocity: u8 },
+    #[cmp_by(header.seq, _fields)]
+    #[hash_by(header.seq)]
+    Sysex {
+        header: Header,
+        #[cmp_by]
+        port: u8,
+        data: Vec<u8>,
+    },
+    #[cmp_by(1)]
+    #[hash_by(1)]
+    Control(u8, u8),
+}
+
+#[derive(EqBy)]
+enum Packet {
+    #[eq_by(header.id)]
+    Data { header: Header, payload: Vec<u8> },
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    let note = |pitch, velocity| Message::NoteOn { pitch, velocity };
+    assert!(note(100, 1) < note(0, 2));
+    assert!(note(2, 1) < note(1, 1));
+
+    let sysex = |seq, port, data| Message::Sysex {
+        header: Header { seq, id: 0 },
+        port,
+        data,
+    };
+    assert!(sysex(1, 9, vec![]) < sysex(2, 0, vec![]));
+    assert!(sysex(1, 0, vec![9]) < sysex(1, 1, vec![]));
+    assert!(sysex(1, 0, vec![9]) == sysex(1, 0, vec![]));
+
+    assert!(Message::Control(9, 0) < Message::Control(0, 1));
+    assert!(note(0, 0) < Message::Control(0, 0));
+
+    let mut hasher = DefaultHasher::new();
+    hasher.write_usize(0);
+    2u8.hash(&mut hasher);
+    1u8.hash(&mut hasher);
+    assert_eq!(hash(&note(1, 2)), hasher.finish());
+    assert_eq!(hash(&sysex(1, 0, vec![9])), hash(&sysex(1, 5, vec![])));
+
+    let packet = |id, payload| Packet::Data {
+        header: Header { seq: 0, id },
+        payload,
+    };
+    assert!(packet(1, vec![1]) == packet(1, vec![2]));
+    assert!(packet(1, vec![1]) != packet(2, vec![1]));
+}
@@ -0,0 +1,59 @@
+use cmp_by_derive::{CmpBy, EqBy, HashBy};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(CmpBy, HashBy, Debug)]
+#[cmp_by(priority(), _variant, _fields)]
+#[hash_by(priority(), _variant)]
+enum Job {
+    Build(#[cmp_by] u32),
+    Test {
+        #[cmp_by]
+        #[hash_by]
+        suite: u32,
+    },
+}
+
+impl Job {
+    fn priority(&self) -> u8 {
+        match self {
+            Job::Build(n) | Job::Test { suite: n } => (*n % 2) as u8,
+        }
+    }
+}
+
+#[derive(CmpBy)]
+#[cmp_by(_fields, _variant)]
+enum Fields {
+    A(#[cmp_by] u8),
+    B(#[cmp_by] u8),
+}
+
+#[derive(EqBy)]
+#[eq_by(_variant)]
+enum Kind {
+    A(#[allow(dead_code)] f32),
+    B,
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    // The priority comes first, then the variant.
+    assert!(Job::Test { suite: 2 } < Job::Build(1));
+    assert!(Job::Build(2) < Job::Test { suite: 0 });
+    assert!(Job::Build(2) < Job::Build(4));
+    assert_eq!(hash(&Job::Test { suite: 2 }), hash(&Job::Test { suite: 2 }));
+
+    // Fields of different variants are not compared, the variant decides.
+    assert!(Fields::A(2) < Fields::B(1));
+    assert!(Fields::A(1) < Fields::A(2));
+    assert!(Fields::A(1) != Fields::B(1));
+
+    assert!(Kind::A(1.0) == Kind::A(2.0));
+    assert!(Kind::A(1.0) != Kind::B);
+}
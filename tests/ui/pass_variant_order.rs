@@ -0,0 +1,44 @@
+use cmp_by_derive::CmpBy;
+
+#[derive(CmpBy)]
+#[cmp_by(variant_order = [Critical, Warning, Info])]
+enum Severity {
+    Info,
+    Warning(#[cmp_by] u8),
+    Critical { #[cmp_by] code: u16 },
+}
+
+#[derive(CmpBy)]
+enum Status {
+    #[cmp_by(rank = 10)]
+    Done,
+    #[cmp_by(rank = 0)]
+    Todo,
+    #[cmp_by(rank = 5)]
+    InProgress(#[cmp_by] u8),
+}
+
+#[derive(CmpBy)]
+enum Large {
+    #[cmp_by(rank = 3000000000)]
+    A,
+    #[cmp_by(rank = 1)]
+    B,
+    #[cmp_by(rank = 18446744073709551615)]
+    C,
+}
+
+fn main() {
+    assert!(Severity::Critical { code: 9 } < Severity::Warning(0));
+    assert!(Severity::Warning(9) < Severity::Info);
+    assert!(Severity::Warning(1) < Severity::Warning(2));
+    assert!(Severity::Info == Severity::Info);
+    assert!(Severity::Info != Severity::Warning(0));
+
+    assert!(Status::Todo < Status::InProgress(0));
+    assert!(Status::InProgress(100) < Status::Done);
+    assert!(Status::Done > Status::Todo);
+
+    assert!(Large::B < Large::A);
+    assert!(Large::A < Large::C);
+}
@@ -0,0 +1,66 @@
+use cmp_by_derive::{CmpBy, HashBy};
+use core::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+fn cmp_ci(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+fn hash_ci<H: Hasher>(a: &str, state: &mut H) {
+    a.to_lowercase().hash(state)
+}
+
+fn hash_bits<H: Hasher>(a: &f32, state: &mut H) {
+    a.to_bits().hash(state)
+}
+
+#[derive(CmpBy, HashBy)]
+#[cmp_by((name(), with = cmp_ci))]
+#[hash_by((name(), with = hash_ci))]
+struct Tag {
+    raw: String,
+}
+
+impl Tag {
+    fn name(&self) -> &str {
+        &self.raw
+    }
+}
+
+#[derive(CmpBy, HashBy)]
+enum Reading {
+    Value(
+        #[cmp_by(with = f32::total_cmp)]
+        #[hash_by(with = hash_bits)]
+        f32,
+    ),
+    Labeled {
+        #[cmp_by(desc, with = cmp_ci)]
+        #[hash_by(with = hash_ci)]
+        label: String,
+    },
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    let tag = |raw: &str| Tag {
+        raw: raw.to_string(),
+    };
+    assert_eq!(tag("abc").cmp(&tag("ABC")), Ordering::Equal);
+    assert_eq!(tag("abc").cmp(&tag("ABD")), Ordering::Less);
+    assert_eq!(hash(&tag("abc")), hash(&tag("ABC")));
+
+    assert_eq!(Reading::Value(1.0).cmp(&Reading::Value(2.0)), Ordering::Less);
+    assert_eq!(hash(&Reading::Value(1.0)), hash(&Reading::Value(1.0)));
+    let label = |label: &str| Reading::Labeled {
+        label: label.to_string(),
+    };
+    assert_eq!(label("a").cmp(&label("B")), Ordering::Greater);
+    assert_eq!(hash(&label("a")), hash(&label("A")));
+}